@@ -0,0 +1,86 @@
+//explicit returns everywhere, on purpose
+#![allow(clippy::needless_return)]
+
+//particle steps per second for every registered system, on one core and on all of them
+//once stepping particles one by one (array of Coords), once through the structure-of-arrays Ensemble
+//run with `cargo bench`, or `cargo bench -- <integrator>` to use something other than rk4 (e.g. `euler`, `dormand`)
+
+use std::hint::black_box;
+use std::thread;
+use std::time::Instant;
+
+use chaos_core::chaos_equations::Coord;
+use chaos_core::ensemble::Ensemble;
+use chaos_core::integrators::{self, Integrator};
+use chaos_core::registry::{ChaosSystem, Registry};
+
+const PARTICLES: usize = 20_000;
+const STEPS: usize = 100;
+const DT: f64 = 0.001;
+
+fn ensemble() -> Vec<Coord> {
+    return (0..PARTICLES)
+        .map(|i| Coord { x: 0.1 + i as f64 * 1e-5, y: 0.2, z: 0.3 })
+        .collect();
+}
+
+fn step_chunk(integrator: &dyn Integrator, f: &(dyn Fn(&Coord) -> Coord + Sync), chunk: &mut [Coord]) {
+    for p in chunk {
+        for _ in 0..STEPS {
+            *p = integrator.step(f, p, DT);
+        }
+    }
+}
+
+//particle steps per second
+fn run(threads: usize, integrator: &dyn Integrator, f: &(dyn Fn(&Coord) -> Coord + Sync)) -> f64 {
+    let mut particles = ensemble();
+    let chunk_size = PARTICLES.div_ceil(threads);
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for chunk in particles.chunks_mut(chunk_size) {
+            scope.spawn(move || step_chunk(integrator, f, chunk));
+        }
+    });
+    let elapsed = start.elapsed().as_secs_f64();
+    black_box(&particles);
+    return (PARTICLES * STEPS) as f64 / elapsed;
+}
+
+//same thing through the ensemble
+fn run_ensemble(threads: usize, integrator: &dyn Integrator, system: &ChaosSystem, params: &[f64]) -> f64 {
+    let mut particles: Ensemble = ensemble().into_iter().collect();
+    let start = Instant::now();
+    particles.step_parallel(threads, system, params, integrator, DT, STEPS);
+    let elapsed = start.elapsed().as_secs_f64();
+    black_box(&particles);
+    return (PARTICLES * STEPS) as f64 / elapsed;
+}
+
+fn main() {
+    let wanted = std::env::args()
+        .skip(1)
+        .find(|a| !a.starts_with("--"))
+        .unwrap_or("rk4".to_string());
+    let integrator = integrators::find(&wanted).unwrap_or_else(|| panic!("no integrator matching `{wanted}`"));
+    let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+    println!("{} integrator, {} particles x {} steps, {} threads", integrator.name(), PARTICLES, STEPS, threads);
+    println!(
+        "{:<24} {:>16} {:>16} {:>16} {:>16}",
+        "system", "1 thread", format!("{threads} threads"), "ensemble 1", format!("ensemble {threads}"),
+    );
+    let registry = Registry::builtin();
+    for system in registry.iter() {
+        let params = system.default_params();
+        let f = |at: &Coord| system.derivative(at, &params);
+        let serial = run(1, integrator, &f);
+        let parallel = run(threads, integrator, &f);
+        let soa_serial = run_ensemble(1, integrator, system, &params);
+        let soa_parallel = run_ensemble(threads, integrator, system, &params);
+        println!(
+            "{:<24} {:>12.2} M/s {:>12.2} M/s {:>12.2} M/s {:>12.2} M/s",
+            system.id, serial / 1e6, parallel / 1e6, soa_serial / 1e6, soa_parallel / 1e6,
+        );
+    }
+}
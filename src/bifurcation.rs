@@ -0,0 +1,225 @@
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::chaos_equations::Coord;
+use crate::export::RunInfo;
+use crate::integrators::Integrator;
+use crate::poincare::Axis;
+use crate::registry::ChaosSystem;
+
+/*
+    Bifurcation Diagrams
+    one parameter swept over a range; for every value the system runs past its transient, then every local maximum
+    of one coordinate gets kept - a single value means a simple loop, two a loop that goes around twice, and so on,
+    until the period doublings pile up and it's a smear: chaos
+*/
+
+//a column can't keep more maxima than this, past that it's a smear either way
+pub const MAXIMA_PER_COLUMN: usize = 2000;
+
+pub struct Sweep {
+    //index of the swept parameter
+    pub param: usize,
+    pub from: f64,
+    pub to: f64,
+    //how many values to try, evenly spaced from `from` to `to`
+    pub columns: usize,
+    //whose maxima to keep
+    pub axis: Axis,
+    //where every column starts
+    pub start: Coord,
+    pub dt: f64,
+    //steps thrown away before looking for maxima, then steps looked at
+    pub transient: usize,
+    pub steps: usize,
+}
+
+//the maxima at one value of the parameter
+pub struct Column {
+    pub value: f64,
+    pub maxima: Vec<f64>,
+}
+
+//the top of the parabola through three evenly spaced samples, the middle one being the largest
+fn refine_peak(y0: f64, y1: f64, y2: f64) -> f64 {
+    let curvature = y0 - y1 * 2.0 + y2;
+    if curvature == 0.0 {
+        return y1;
+    }
+    let offset = (y0 - y2) / (curvature * 2.0);
+    return y1 - (y0 - y2) * offset / 4.0;
+}
+
+impl Sweep {
+    pub fn value(&self, column: usize) -> f64 {
+        if self.columns < 2 {
+            return self.from;
+        }
+        return self.from + (self.to - self.from) * column as f64 / (self.columns - 1) as f64;
+    }
+
+    pub fn column(&self, system: &ChaosSystem, params: &[f64], integrator: &dyn Integrator, column: usize) -> Column {
+        let mut params = params.to_vec();
+        let value = self.value(column);
+        params[self.param] = value;
+        let f = |c: &Coord| system.derivative(c, &params);
+
+        let mut at = self.start;
+        for _ in 0..self.transient {
+            at = integrator.step(&f, &at, self.dt);
+        }
+        let mut maxima = Vec::new();
+        let (mut y0, mut y1) = (f64::NAN, self.axis.of(&at));
+        for _ in 0..self.steps {
+            at = integrator.step(&f, &at, self.dt);
+            let y2 = self.axis.of(&at);
+            //gone off to infinity, nothing more to find
+            if !y2.is_finite() || maxima.len() >= MAXIMA_PER_COLUMN {
+                break;
+            }
+            if y1 > y0 && y1 >= y2 {
+                maxima.push(refine_peak(y0, y1, y2));
+            }
+            (y0, y1) = (y1, y2);
+        }
+        return Column { value, maxima };
+    }
+
+    //every column, spread over `threads` threads; `done` counts finished columns as they finish
+    pub fn run(&self, system: &ChaosSystem, params: &[f64], integrator: &dyn Integrator, threads: usize, done: &AtomicUsize) -> Vec<Column> {
+        let threads = threads.clamp(1, self.columns.max(1));
+        let mut columns: Vec<Column> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|t| {
+                    //every thread takes every nth column, so cheap and expensive stretches get shared out
+                    return scope.spawn(move || {
+                        return (t..self.columns).step_by(threads)
+                            .map(|i| {
+                                let column = self.column(system, params, integrator, i);
+                                done.fetch_add(1, Ordering::Relaxed);
+                                return column;
+                            })
+                            .collect::<Vec<_>>();
+                    });
+                })
+                .collect();
+            return handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+        });
+        columns.sort_by(|a, b| a.value.total_cmp(&b.value));
+        return columns;
+    }
+}
+
+/*
+    Output
+*/
+
+//one pixel column per Column, `height` tall, the maxima's range top to bottom - grayscale, darker where more maxima landed
+//returns the pixels row by row and the (lowest, highest) maximum
+pub fn render(columns: &[Column], height: usize) -> (Vec<u8>, (f64, f64)) {
+    let width = columns.len();
+    let all = || columns.iter().flat_map(|c| c.maxima.iter().copied());
+    let lo = all().fold(f64::INFINITY, f64::min);
+    let hi = all().fold(f64::NEG_INFINITY, f64::max);
+    let span = if hi > lo { hi - lo } else { 1.0 };
+
+    let mut counts = vec![0u32; width * height];
+    for (x, column) in columns.iter().enumerate() {
+        for m in &column.maxima {
+            let row = ((hi - m) / span * (height - 1) as f64).round() as usize;
+            counts[row.min(height - 1) * width + x] += 1;
+        }
+    }
+    //log scale, so one stray maximum still shows up next to a thousand on the same spot
+    let most = (counts.iter().copied().max().unwrap_or(0) as f64).ln_1p().max(f64::EPSILON);
+    let pixels = counts.iter()
+        .map(|&c| if c == 0 { 255 } else { (200.0 * (1.0 - (c as f64).ln_1p() / most)) as u8 })
+        .collect();
+    return (pixels, (lo, hi));
+}
+
+pub fn write_png(out: impl Write, pixels: &[u8], width: usize, height: usize) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(pixels).map_err(io::Error::other)?;
+    return writer.finish().map_err(io::Error::other);
+}
+
+//one row per maximum, `<param>,<axis>_max`, under the same `# key: value` lines as a trajectory csv
+pub fn write_csv(mut out: impl Write, info: &RunInfo, param: &str, axis: Axis, columns: &[Column]) -> io::Result<()> {
+    for (key, value) in info.entries() {
+        writeln!(out, "# {key}: {value}")?;
+    }
+    writeln!(out, "{param},{}_max", axis.name())?;
+    for column in columns {
+        for m in &column.maxima {
+            writeln!(out, "{},{m}", column.value)?;
+        }
+    }
+    return out.flush();
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrators;
+    use crate::registry::Registry;
+
+    fn rossler_sweep(from: f64, to: f64, columns: usize) -> Vec<Column> {
+        let registry = Registry::builtin();
+        let rossler = registry.get(registry.find("rossler_variant1").unwrap());
+        let sweep = Sweep {
+            param: rossler.param_index("c").unwrap(),
+            from,
+            to,
+            columns,
+            axis: Axis::X,
+            start: Coord { x: 1.0, y: 1.0, z: 1.0 },
+            dt: 0.01,
+            transient: 50_000,
+            steps: 20_000,
+        };
+        return sweep.run(rossler, &rossler.default_params(), integrators::find("rk4").unwrap(), 2, &AtomicUsize::new(0));
+    }
+
+    //how many different maxima, telling apart ones further than `tolerance` from each other
+    fn distinct(maxima: &[f64], tolerance: f64) -> usize {
+        let mut sorted = maxima.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        return sorted.windows(2).filter(|w| w[1] - w[0] > tolerance).count() + 1;
+    }
+
+    #[test]
+    fn rossler_period_doubling() {
+        //the textbook cascade with a = b = 0.1: one loop at c = 4, two at 6, four at 8
+        for (c, period) in [(4.0, 1), (6.0, 2), (8.0, 4)] {
+            let columns = rossler_sweep(c, c, 1);
+            assert_eq!(distinct(&columns[0].maxima, 1e-3), period, "c = {c}");
+        }
+    }
+
+    #[test]
+    fn renders_a_png() {
+        let columns = rossler_sweep(4.0, 8.5, 4);
+        assert_eq!(columns.iter().map(|c| c.value).collect::<Vec<_>>(), [4.0, 5.5, 7.0, 8.5]);
+        let (pixels, (lo, hi)) = render(&columns, 16);
+        assert_eq!(pixels.len(), 4 * 16);
+        assert!(lo < hi);
+        //the top and bottom rows each have a maximum in them
+        assert!(pixels[..4].iter().any(|&p| p < 255) && pixels[15 * 4..].iter().any(|&p| p < 255));
+
+        let mut file = Vec::new();
+        write_png(&mut file, &pixels, 4, 16).unwrap();
+        let mut reader = png::Decoder::new(file.as_slice()).read_info().unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut decoded).unwrap();
+        assert_eq!(decoded, pixels);
+    }
+}
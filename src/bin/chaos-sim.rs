@@ -0,0 +1,324 @@
+//explicit returns everywhere, on purpose
+#![allow(clippy::needless_return)]
+
+//headless batch simulation - no window, no gpu, just trajectories as csv, npy or parquet
+//`cargo run --bin chaos-sim -- --help` for the options
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::atomic::AtomicUsize;
+use std::thread;
+
+use chaos_core::bifurcation::{self, Sweep};
+use chaos_core::chaos_equations::Coord;
+use chaos_core::ensemble::Ensemble;
+use chaos_core::export::{self, CsvWriter, Format, Row, RunInfo, TrajectoryWriter};
+use chaos_core::integrators::{self, Integrator};
+use chaos_core::lyapunov;
+use chaos_core::poincare::Axis;
+use chaos_core::registry::{ChaosSystem, Registry};
+use chaos_core::user_systems;
+
+const USAGE: &str = "\
+usage: chaos-sim [options]
+
+  --system <id>          which system to run (default lorenz), see --list
+  --equations <file>     also load the systems typed in the ron file, see src/user_systems.rs
+  --param <name>=<value> override a parameter, can be repeated
+  --init <x>,<y>,<z>     starting point of a particle, can be repeated (default 1,1,1)
+  --dt <seconds>         time step (default: the system's own)
+  --steps <n>            how many steps to take (default 10000)
+  --every <n>            only write every nth step (default 1)
+  --integrator <name>    euler, midpoint, rk4, rk45 or dormand (default rk4)
+  --out <file>           write there instead of stdout
+  --format <format>      csv, npy or parquet (default: from the --out extension, else csv)
+                         npy and parquet need --out; npy gets the run info in a .json next to it
+  --lyapunov             instead of the trajectories, print the lyapunov exponents from every --init
+                         measured over --steps steps, after another tenth of that to settle
+  --sweep <name>=<a>:<b>  instead of the trajectories, a bifurcation diagram: the maxima of one coordinate
+                         as parameter <name> goes from a to b - a png if --out ends in .png, else csv
+                         every value starts from the first --init and runs --steps steps, the first half to settle
+  --columns <n>          how many values of the parameter to try, one pixel column each (default 800)
+  --height <n>           height of the png in pixels (default 600)
+  --of <x|y|z>           whose maxima to plot (default z)
+  --list                 list the systems and their parameters
+  --help                 this
+
+one row per particle per written step: step,t,particle,x,y,z
+with --lyapunov one row per --init: x,y,z,l1,l2,l3, largest exponent first
+with --sweep one row per maximum: <name>,<x|y|z>_max";
+
+struct Options {
+    system: usize,
+    params: Vec<f64>,
+    init: Vec<Coord>,
+    dt: f64,
+    steps: usize,
+    every: usize,
+    integrator: &'static dyn Integrator,
+    out: Option<String>,
+    format: Format,
+    lyapunov: bool,
+    sweep: Option<SweepOptions>,
+}
+
+struct SweepOptions {
+    param: usize,
+    from: f64,
+    to: f64,
+    columns: usize,
+    height: usize,
+    axis: Axis,
+}
+
+enum Command {
+    Run(Options),
+    List,
+    Help,
+}
+
+fn parse_f64(flag: &str, s: &str) -> Result<f64, String> {
+    return s.trim().parse().map_err(|_| format!("{flag}: `{s}` isn't a number"));
+}
+
+fn parse_usize(flag: &str, s: &str) -> Result<usize, String> {
+    return s.trim().parse().map_err(|_| format!("{flag}: `{s}` isn't a whole number"));
+}
+
+fn parse_coord(s: &str) -> Result<Coord, String> {
+    let parts = s.split(',').map(|p| parse_f64("--init", p)).collect::<Result<Vec<_>, _>>()?;
+    let [x, y, z] = parts[..] else {
+        return Err(format!("--init: expected x,y,z, got `{s}`"));
+    };
+    return Ok(Coord { x, y, z });
+}
+
+fn parse_args(registry: &Registry, mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut system = None;
+    let mut overrides = Vec::new();
+    let mut init = Vec::new();
+    let mut dt = None;
+    let mut steps = 10_000;
+    let mut every = 1;
+    let mut integrator = integrators::find("rk4").unwrap();
+    let mut out = None;
+    let mut format = None;
+    let mut lyapunov = false;
+    let mut sweep = None;
+    let mut columns = 800;
+    let mut height = 600;
+    let mut axis = Axis::Z;
+
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or(format!("{flag} needs a value"));
+        match flag.as_str() {
+            "--system" => {
+                let id = value()?;
+                system = Some(registry.find(&id).ok_or(format!("no system called `{id}`, see --list"))?);
+            }
+            "--param" => {
+                let v = value()?;
+                let (name, x) = v.split_once('=').ok_or(format!("--param: expected name=value, got `{v}`"))?;
+                overrides.push((name.to_string(), parse_f64("--param", x)?));
+            }
+            //already loaded by main
+            "--equations" => drop(value()?),
+            "--init" => init.push(parse_coord(&value()?)?),
+            "--dt" => dt = Some(parse_f64("--dt", &value()?)?),
+            "--steps" => steps = parse_usize("--steps", &value()?)?,
+            "--every" => every = parse_usize("--every", &value()?)?.max(1),
+            "--integrator" => {
+                let name = value()?;
+                integrator = integrators::find(&name).ok_or(format!("no integrator matching `{name}`"))?;
+            }
+            "--out" => out = Some(value()?),
+            "--format" => {
+                let name = value()?.to_lowercase();
+                format = Some(Format::ALL.into_iter().find(|f| f.name() == name).ok_or(format!("unknown format `{name}`"))?);
+            }
+            "--lyapunov" => lyapunov = true,
+            "--sweep" => {
+                let v = value()?;
+                let parsed = v.split_once('=').and_then(|(name, range)| Some((name, range.split_once(':')?)));
+                let Some((name, (a, b))) = parsed else {
+                    return Err(format!("--sweep: expected name=from:to, got `{v}`"));
+                };
+                sweep = Some((name.to_string(), parse_f64("--sweep", a)?, parse_f64("--sweep", b)?));
+            }
+            "--columns" => columns = parse_usize("--columns", &value()?)?.max(1),
+            "--height" => height = parse_usize("--height", &value()?)?.max(2),
+            "--of" => {
+                let name = value()?.to_lowercase();
+                axis = Axis::ALL.into_iter().find(|a| a.name() == name).ok_or(format!("--of: expected x, y or z, got `{name}`"))?;
+            }
+            "--list" => return Ok(Command::List),
+            "--help" | "-h" => return Ok(Command::Help),
+            _ => return Err(format!("unknown option `{flag}`")),
+        }
+    }
+
+    let system = system.unwrap_or_else(|| registry.find("lorenz").unwrap());
+    let def = registry.get(system);
+    let mut params = def.default_params();
+    for (name, x) in overrides {
+        let i = def.param_index(&name).ok_or(format!("{} has no parameter `{name}`", def.id))?;
+        params[i] = x;
+    }
+    let sweep = match sweep {
+        Some((name, from, to)) => Some(SweepOptions {
+            param: def.param_index(&name).ok_or(format!("{} has no parameter `{name}`", def.id))?,
+            from,
+            to,
+            columns,
+            height,
+            axis,
+        }),
+        None => None,
+    };
+    let format = format
+        .or_else(|| out.as_deref().and_then(|o| Format::from_path(Path::new(o))))
+        .unwrap_or(Format::Csv);
+    if format != Format::Csv && out.is_none() && sweep.is_none() {
+        return Err(format!("{} output needs --out", format.name()));
+    }
+    if init.is_empty() {
+        init.push(Coord { x: 1.0, y: 1.0, z: 1.0 });
+    }
+    return Ok(Command::Run(Options {
+        system,
+        params,
+        init,
+        dt: dt.unwrap_or(def.dt),
+        steps,
+        every,
+        integrator,
+        out,
+        format,
+        lyapunov,
+        sweep,
+    }));
+}
+
+fn list(registry: &Registry) {
+    for system in registry.iter() {
+        let params: Vec<String> = system.params.iter()
+            .map(|p| format!("{}={} [{}, {}]", p.name, p.default, p.min, p.max))
+            .collect();
+        println!("{:<24} {:<24} {}", system.id, system.name, params.join(" "));
+    }
+}
+
+fn write_rows(out: &mut dyn TrajectoryWriter, ensemble: &Ensemble, step: usize, dt: f64) -> io::Result<()> {
+    for (i, at) in ensemble.iter().enumerate() {
+        out.write(&Row { step: step as u64, t: step as f64 * dt, particle: i as u64, at })?;
+    }
+    return Ok(());
+}
+
+fn run(system: &ChaosSystem, opts: &Options, out: &mut dyn TrajectoryWriter) -> io::Result<()> {
+    let mut ensemble: Ensemble = opts.init.iter().copied().collect();
+    write_rows(out, &ensemble, 0, opts.dt)?;
+    let mut step = 0;
+    while step < opts.steps {
+        let n = opts.every.min(opts.steps - step);
+        ensemble.step(system, &opts.params, opts.integrator, opts.dt, n);
+        step += n;
+        write_rows(out, &ensemble, step, opts.dt)?;
+    }
+    return out.finish();
+}
+
+fn print_lyapunov(system: &ChaosSystem, opts: &Options) {
+    println!("x,y,z,l1,l2,l3");
+    for at in &opts.init {
+        let [l1, l2, l3] = lyapunov::spectrum(system, &opts.params, opts.integrator, *at, opts.dt, opts.steps / 10, opts.steps);
+        println!("{},{},{},{l1},{l2},{l3}", at.x, at.y, at.z);
+    }
+}
+
+fn bifurcation(system: &ChaosSystem, opts: &Options, sweep_opts: &SweepOptions) -> io::Result<()> {
+    let sweep = Sweep {
+        param: sweep_opts.param,
+        from: sweep_opts.from,
+        to: sweep_opts.to,
+        columns: sweep_opts.columns,
+        axis: sweep_opts.axis,
+        start: opts.init[0],
+        dt: opts.dt,
+        transient: opts.steps / 2,
+        steps: opts.steps - opts.steps / 2,
+    };
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let columns = sweep.run(system, &opts.params, opts.integrator, threads, &AtomicUsize::new(0));
+
+    let png = opts.out.as_deref().is_some_and(|o| o.to_lowercase().ends_with(".png"));
+    if png {
+        let (pixels, (lo, hi)) = bifurcation::render(&columns, sweep_opts.height);
+        let file = BufWriter::new(File::create(opts.out.as_deref().unwrap())?);
+        bifurcation::write_png(file, &pixels, columns.len(), sweep_opts.height)?;
+        //the png itself has no axes, so say what it spans
+        eprintln!(
+            "{} from {} (left) to {} (right), {} max from {lo} (bottom) to {hi} (top)",
+            system.params[sweep.param].name, sweep.from, sweep.to, sweep.axis.name(),
+        );
+        return Ok(());
+    }
+    let info = RunInfo::new(system, &opts.params, opts.dt, opts.integrator);
+    let name = system.params[sweep.param].name;
+    return match &opts.out {
+        Some(path) => bifurcation::write_csv(BufWriter::new(File::create(path)?), &info, name, sweep.axis, &columns),
+        None => bifurcation::write_csv(BufWriter::new(io::stdout().lock()), &info, name, sweep.axis, &columns),
+    };
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut registry = Registry::builtin();
+    //before the rest of the options, so --system can pick one of them
+    if let Some(path) = args.windows(2).find(|w| w[0] == "--equations").map(|w| &w[1]) {
+        if let Err(e) = user_systems::load(Path::new(path), &mut registry) {
+            eprintln!("error: {e}");
+            return ExitCode::from(2);
+        }
+    }
+    let opts = match parse_args(&registry, args.into_iter()) {
+        Ok(Command::Run(opts)) => opts,
+        Ok(Command::List) => {
+            list(&registry);
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Help) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let system = registry.get(opts.system);
+    if opts.lyapunov {
+        print_lyapunov(system, &opts);
+        return ExitCode::SUCCESS;
+    }
+    if let Some(sweep) = &opts.sweep {
+        if let Err(e) = bifurcation(system, &opts, sweep) {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+        return ExitCode::SUCCESS;
+    }
+    let info = RunInfo::new(system, &opts.params, opts.dt, opts.integrator);
+    let result = match &opts.out {
+        Some(path) => export::create(Path::new(path), opts.format, &info).and_then(|mut w| run(system, &opts, &mut *w)),
+        None => CsvWriter::new(BufWriter::new(io::stdout().lock()), &info).and_then(|mut w| run(system, &opts, &mut w)),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+    return ExitCode::SUCCESS;
+}
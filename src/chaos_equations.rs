@@ -1,14 +1,27 @@
-use std::ops::Add;
+use std::ops::{Add, Mul, Sub};
+
+use crate::lanes::{Real, F64x4};
 
 //a bit redundant - just a (x,y,z) object
+//usually of f64s, but the equations also run on F64x4s to move four particles at once (see lanes.rs)
 #[derive(Clone, Copy)]
-pub struct Coord {
-    pub x:f64,
-    pub y:f64,
-    pub z:f64
+pub struct Coord<T = f64> {
+    pub x:T,
+    pub y:T,
+    pub z:T
+}
+
+impl Coord {
+    pub const ZERO: Coord = Coord { x: 0.0, y: 0.0, z: 0.0 };
+}
+
+impl<T: Real> Coord<T> {
+    pub fn splat(c: &Coord) -> Self {
+        return Coord { x: T::splat(c.x), y: T::splat(c.y), z: T::splat(c.z) };
+    }
 }
 
-impl Add for Coord {
+impl<T: Real> Add for Coord<T> {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
@@ -20,18 +33,45 @@ impl Add for Coord {
     }
 }
 
-//any equation with this singiture can be used for determining the particle movement
-pub type ChaosEq = fn(&Coord, dt: f64) -> Coord;
+impl<T: Real> Sub for Coord<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+impl<T: Real> Mul<f64> for Coord<T> {
+    type Output = Self;
+
+    fn mul(self, k: f64) -> Self {
+        Self {
+            x: self.x * k,
+            y: self.y * k,
+            z: self.z * k,
+        }
+    }
+}
+
+//any equation with this signature can be used for determining the particle movement
+//it returns the derivative (dx/dt, dy/dt, dz/dt) at the given point - see integrators.rs for how it's stepped
+//the slice holds the system's parameter values, in the order its registry entry lists them (see registry.rs)
+pub type ChaosEq = fn(&Coord, &[f64]) -> Coord;
+
+//the same equation, four particles at a time
+pub type LaneEq = fn(&Coord<F64x4>, &[f64]) -> Coord<F64x4>;
+
+//every equation below is generic over Real, so constants are written after the variables (`x * 2.0`)
 
 //boids - todo?
 
-//move with constant speed
-pub fn basic_equation(at: &Coord, dt: f64)->Coord {
-    return Coord {
-        x: at.x,// + 1.0 * dt,
-        y: at.y,// + 1.0 * dt,
-        z: at.z,// + 1.0 * dt,
-    };
+//stand still
+pub fn basic_equation<T: Real>(_at: &Coord<T>, _params: &[f64])->Coord<T> {
+    return Coord { x: T::splat(0.0), y: T::splat(0.0), z: T::splat(0.0) };
 }
 
 /*
@@ -39,48 +79,457 @@ pub fn basic_equation(at: &Coord, dt: f64)->Coord {
 */
 
 //a generalized lorenz attractor equation
-pub fn lorenz_attractor_general(at: &Coord, dt: f64, ro: f64, sigma: f64, beta: f64)->Coord {
+pub fn lorenz_attractor_general<T: Real>(at: &Coord<T>, ro: f64, sigma: f64, beta: f64)->Coord<T> {
     return Coord {
-        x: at.x + (sigma * (at.y - at.x)) * dt,
-        y: at.y + (at.x * (ro - at.z) - at.z) * dt,
-        z: at.z + (at.x * at.y - beta * at.z) * dt,
+        x: (at.y - at.x) * sigma,
+        y: at.x * (-at.z + ro) - at.y,
+        z: at.x * at.y - at.z * beta,
     };
 }
 
-//lorenz attractor with standard values for ro, sigma and beta
-pub fn lorenz_attractor_standard(at: &Coord, dt: f64)->Coord {
-    let ro = 28.0;
-    let sigma = 10.0;
-    let beta = 8.0/3.0;
+/*
+    Rössler Attractor Family
+*/
 
-    return lorenz_attractor_general(at, dt, ro, sigma, beta);
+//a generalized rössler attractor equation
+pub fn rossler_attractor_general<T: Real>(at: &Coord<T>, a: f64, b: f64, c: f64)->Coord<T> {
+    return Coord {
+        x: - at.y - at.z,
+        y: at.x + at.y * a,
+        z: at.z * (at.x - c) + b,
+    };
 }
 
 /*
-    Rössler Attractor Family
+    Lorenz-like Systems
 */
 
-//a generalized rössler attractor equation
-pub fn rossler_attractor_general(at: &Coord, dt: f64, a: f64, b: f64, c: f64)->Coord {
+pub fn chen_attractor<T: Real>(at: &Coord<T>, a: f64, b: f64, c: f64)->Coord<T> {
+    return Coord {
+        x: (at.y - at.x) * a,
+        y: at.x * (c - a) - at.x * at.z + at.y * c,
+        z: at.x * at.y - at.z * b,
+    };
+}
+
+pub fn lu_attractor<T: Real>(at: &Coord<T>, a: f64, b: f64, c: f64)->Coord<T> {
+    return Coord {
+        x: (at.y - at.x) * a,
+        y: - at.x * at.z + at.y * c,
+        z: at.x * at.y - at.z * b,
+    };
+}
+
+//lorenz's 1984 model of the general atmospheric circulation
+pub fn lorenz84_attractor<T: Real>(at: &Coord<T>, a: f64, b: f64, f: f64, g: f64)->Coord<T> {
+    return Coord {
+        x: - at.x * a - at.y * at.y - at.z * at.z + a * f,
+        y: - at.y + at.x * at.y - at.x * at.z * b + g,
+        z: - at.z + at.x * at.y * b + at.x * at.z,
+    };
+}
+
+pub fn burke_shaw_attractor<T: Real>(at: &Coord<T>, s: f64, v: f64)->Coord<T> {
+    return Coord {
+        x: - (at.x + at.y) * s,
+        y: - at.y - at.x * at.z * s,
+        z: at.x * at.y * s + v,
+    };
+}
+
+/*
+    Cyclically Symmetric Systems
+*/
+
+pub fn thomas_attractor<T: Real>(at: &Coord<T>, b: f64)->Coord<T> {
+    return Coord {
+        x: at.y.sin() - at.x * b,
+        y: at.z.sin() - at.y * b,
+        z: at.x.sin() - at.z * b,
+    };
+}
+
+pub fn halvorsen_attractor<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: - at.x * a - at.y * 4.0 - at.z * 4.0 - at.y * at.y,
+        y: - at.y * a - at.z * 4.0 - at.x * 4.0 - at.z * at.z,
+        z: - at.z * a - at.x * 4.0 - at.y * 4.0 - at.x * at.x,
+    };
+}
+
+/*
+    Other Polynomial Systems
+*/
+
+pub fn aizawa_attractor<T: Real>(at: &Coord<T>, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64)->Coord<T> {
+    return Coord {
+        x: (at.z - b) * at.x - at.y * d,
+        y: at.x * d + (at.z - b) * at.y,
+        z: at.z * a + c - at.z * at.z * at.z / 3.0
+            - (at.x * at.x + at.y * at.y) * (at.z * e + 1.0)
+            + at.z * at.x * at.x * at.x * f,
+    };
+}
+
+pub fn dadras_attractor<T: Real>(at: &Coord<T>, a: f64, b: f64, c: f64, d: f64, e: f64)->Coord<T> {
+    return Coord {
+        x: at.y - at.x * a + at.y * at.z * b,
+        y: at.y * c - at.x * at.z + at.z,
+        z: at.x * at.y * d - at.z * e,
+    };
+}
+
+pub fn rabinovich_fabrikant_attractor<T: Real>(at: &Coord<T>, alpha: f64, gamma: f64)->Coord<T> {
+    return Coord {
+        x: at.y * (at.z - 1.0 + at.x * at.x) + at.x * gamma,
+        y: at.x * (at.z * 3.0 + 1.0 - at.x * at.x) + at.y * gamma,
+        z: at.z * (at.x * at.y + alpha) * -2.0,
+    };
+}
+
+pub fn four_wing_attractor<T: Real>(at: &Coord<T>, a: f64, b: f64, c: f64)->Coord<T> {
+    return Coord {
+        x: at.x * a + at.y * at.z,
+        y: at.x * b + at.y * c - at.x * at.z,
+        z: - at.z - at.x * at.y,
+    };
+}
+
+//a thermostatted harmonic oscillator - sprott a is the a=1 case
+pub fn nose_hoover_attractor<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: at.y,
+        y: - at.x + at.y * at.z,
+        z: - at.y * at.y + a,
+    };
+}
+
+/*
+    Chua's Circuit
+*/
+
+//m0/m1 are the inner/outer slopes of the piecewise linear diode
+pub fn chua_circuit<T: Real>(at: &Coord<T>, alpha: f64, beta: f64, m0: f64, m1: f64)->Coord<T> {
+    let diode = at.x * m1 + ((at.x + 1.0).abs() - (at.x - 1.0).abs()) * (0.5 * (m0 - m1));
+    return Coord {
+        x: (at.y - at.x - diode) * alpha,
+        y: at.x - at.y + at.z,
+        z: - at.y * beta,
+    };
+}
+
+/*
+    Sprott Systems
+    (J. C. Sprott, "Some simple chaotic flows", 1994)
+    the lone non-unit coefficients are exposed as parameters
+*/
+
+pub fn sprott_a<T: Real>(at: &Coord<T>)->Coord<T> {
+    return nose_hoover_attractor(at, 1.0);
+}
+
+pub fn sprott_b<T: Real>(at: &Coord<T>)->Coord<T> {
+    return Coord {
+        x: at.y * at.z,
+        y: at.x - at.y,
+        z: - at.x * at.y + 1.0,
+    };
+}
+
+pub fn sprott_c<T: Real>(at: &Coord<T>)->Coord<T> {
+    return Coord {
+        x: at.y * at.z,
+        y: at.x - at.y,
+        z: - at.x * at.x + 1.0,
+    };
+}
+
+pub fn sprott_d<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: - at.y,
+        y: at.x + at.z,
+        z: at.x * at.z + at.y * at.y * a,
+    };
+}
+
+pub fn sprott_e<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: at.y * at.z,
+        y: at.x * at.x - at.y,
+        z: - at.x * a + 1.0,
+    };
+}
+
+pub fn sprott_f<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: at.y + at.z,
+        y: - at.x + at.y * a,
+        z: at.x * at.x - at.z,
+    };
+}
+
+pub fn sprott_g<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: at.x * a + at.z,
+        y: at.x * at.z - at.y,
+        z: - at.x + at.y,
+    };
+}
+
+pub fn sprott_h<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: - at.y + at.z * at.z,
+        y: at.x + at.y * a,
+        z: at.x - at.z,
+    };
+}
+
+pub fn sprott_i<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: - at.y * a,
+        y: at.x + at.z,
+        z: at.x + at.y * at.y - at.z,
+    };
+}
+
+pub fn sprott_j<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: at.z * a,
+        y: - at.y * a + at.z,
+        z: - at.x + at.y + at.y * at.y,
+    };
+}
+
+pub fn sprott_k<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: at.x * at.y - at.z,
+        y: at.x - at.y,
+        z: at.x + at.z * a,
+    };
+}
+
+pub fn sprott_l<T: Real>(at: &Coord<T>, a: f64, b: f64)->Coord<T> {
+    return Coord {
+        x: at.y + at.z * a,
+        y: at.x * at.x * b - at.y,
+        z: - at.x + 1.0,
+    };
+}
+
+pub fn sprott_m<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: - at.z,
+        y: - at.x * at.x - at.y,
+        z: at.x * a + at.y + a,
+    };
+}
+
+pub fn sprott_n<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: - at.y * a,
+        y: at.x + at.z * at.z,
+        z: at.y - at.z * a + 1.0,
+    };
+}
+
+pub fn sprott_o<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: at.y,
+        y: at.x - at.z,
+        z: at.x + at.x * at.z + at.y * a,
+    };
+}
+
+pub fn sprott_p<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
     return Coord {
-        x: at.x + (- at.y - at.z) * dt,
-        y: at.y + (at.x + a * at.y) * dt,
-        z: at.z + (b + at.z * (at.x - c)) * dt,
+        x: at.y * a + at.z,
+        y: - at.x + at.y * at.y,
+        z: at.x + at.y,
     };
 }
 
-pub fn rossler_attractor_variant1(at: &Coord, dt: f64)->Coord {
-    let a = 0.1;
-    let b = 0.1;
-    let c = 14.0;
+pub fn sprott_q<T: Real>(at: &Coord<T>, a: f64, b: f64)->Coord<T> {
+    return Coord {
+        x: - at.z,
+        y: at.x - at.y,
+        z: at.x * a + at.y * at.y + at.z * b,
+    };
+}
+
+pub fn sprott_r<T: Real>(at: &Coord<T>, a: f64, b: f64)->Coord<T> {
+    return Coord {
+        x: - at.y + a,
+        y: at.z + b,
+        z: at.x * at.y - at.z,
+    };
+}
 
-    return rossler_attractor_general(at, dt, a, b, c);
+pub fn sprott_s<T: Real>(at: &Coord<T>, a: f64)->Coord<T> {
+    return Coord {
+        x: - at.x - at.y * a,
+        y: at.x + at.z * at.z,
+        z: at.x + 1.0,
+    };
 }
 
-pub fn rossler_attractor_variant2(at: &Coord, dt: f64)->Coord {
-    let a = 0.2;
-    let b = 0.2;
-    let c = 5.7;
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrators::{Integrator, Rk4, DORMAND_PRINCE};
+    use crate::registry::Registry;
+
+    const PROBE: Coord = Coord { x: 0.7, y: -1.3, z: 2.1 };
+    const START: Coord = Coord { x: 0.1, y: 0.2, z: 0.3 };
+    const REF_DT: f64 = 0.002;
+    const REF_STEPS: usize = 500;
+
+    //(system id, derivative at PROBE, position after REF_STEPS rk4 steps of REF_DT from START), all with default parameters
+    //computed outside of this crate, straight from the published equations
+    const REFERENCE: &[(&str, [f64; 3], [f64; 3])] = &[
+        ("still", [0.0, 0.0, 0.0], [0.1, 0.2, 0.3]),
+        ("lorenz", [-20.0, 19.43, -6.51], [-9.028747694550539, -10.012714171604664, 26.64146813692915]),
+        ("rossler_variant1", [-0.8, 0.57, -27.830000000000002], [-0.14278482494251046, 0.19017347772219542, 0.007078247066178203]),
+        ("rossler_variant2", [-0.8, 0.43999999999999995, -10.3], [-0.19458049543998238, 0.17792361764513148, 0.0349940938008772]),
+        ("chen", [-70.0, -42.769999999999996, -7.210000000000001], [7.248399678892527, 7.682203380713394, 21.528750630809515]),
+        ("lu", [-72.0, -27.47, -7.210000000000001], [13.416024592659605, 16.200124067745826, 18.16503880358216]),
+        ("thomas", [-1.1092883854171929, 1.1338511666488738, 0.20702708723769098], [0.3783436666370002, 0.4765058226562487, 0.44643888766730777]),
+        ("aizawa", [5.529999999999999, 0.6299999999999994, -3.7444700000000006], [-0.02620216134699312, -0.24805483246268256, 1.354754738819868]),
+        ("halvorsen", [-6.213, -13.153, -2.0589999999999997], [-0.6848881874363798, -0.1588663405237001, 0.7646597651027507]),
+        ("dadras", [-10.771, -1.5799999999999996, -20.720000000000002], [0.29507479125623903, 1.2571807981819976, 0.05909265319978683]),
+        ("rabinovich_fabrikant", [-1.997, 4.6370000000000005, 3.2339999999999995], [-0.0865756430355334, 0.25227382058477316, 0.22559029598431882]),
+        ("four_wing", [-2.5900000000000003, -0.943, -1.1900000000000002], [0.15565892222001523, 0.11668744021263985, 0.09797380049079729]),
+        ("chua", [-18.718439999999998, 4.1, 36.4], [1.154098449954057, -0.1890344469433882, -1.3642412793553715]),
+        ("nose_hoover", [-1.3, -3.4300000000000006, -0.6900000000000002], [0.27434722343319545, 0.13969926299248114, 1.2693118686221805]),
+        ("lorenz84", [-4.275, -4.49, -4.2700000000000005], [1.6028423864137087, -0.48428116912543734, 0.06517370236378973]),
+        ("burke_shaw", [6.000000000000001, -13.4, -4.827999999999999], [0.3015533180219746, -0.24985020413882508, 0.01708630726289518]),
+        ("sprott_a", [-1.3, -3.4300000000000006, -0.6900000000000002], [0.27434722343319545, 0.13969926299248114, 1.2693118686221805]),
+        ("sprott_b", [-2.7300000000000004, 2.0, 1.91], [0.23730012661654695, 0.1789267059152506, 1.2728137690976224]),
+        ("sprott_c", [-2.7300000000000004, 2.0, 0.51], [0.23779834043844436, 0.17908557590350507, 1.2742296557049242]),
+        ("sprott_d", [1.3, 2.8, 6.54], [-0.2988461993450732, 0.6084207096030966, 0.7708582324868163]),
+        ("sprott_e", [-2.7300000000000004, 1.79, -1.7999999999999998], [0.1689935087466159, 0.08622335813507902, 0.76276472615488]),
+        ("sprott_f", [0.8, -1.35, -1.61], [0.4351538664590282, -0.03716399619487538, 0.18220340632257928]),
+        ("sprott_g", [2.38, 2.77, -2.0], [0.4848381216189467, 0.12280578709135925, 0.1594631045703241]),
+        ("sprott_h", [5.71, 0.04999999999999993, -1.4000000000000001], [-0.14061622622928122, 0.34094666931487927, 0.09659192295422483]),
+        ("sprott_i", [0.26, 2.8, 0.29000000000000004], [0.025178396397876533, 0.5287713287697189, 0.2561404127839663]),
+        ("sprott_j", [4.2, 4.7, -0.30999999999999983], [0.6099668488476762, 0.12245625799357703, 0.10612945256296237]),
+        ("sprott_k", [-3.01, 2.0, 1.33], [-0.24881023579678288, 0.011411228652549512, 0.3348725961761641]),
+        ("sprott_l", [6.89, 1.741, 0.30000000000000004], [2.203165281812424, 1.2321405896412594, 0.19541093290660758]),
+        ("sprott_m", [-2.1, 0.81, 1.5899999999999996], [-0.9890867004286187, -0.1148839720297423, 1.514492484663225]),
+        ("sprott_n", [2.6, 5.11, -4.5], [-0.43812445074529455, 0.29160728433379546, 0.5953608567944344]),
+        ("sprott_o", [-1.3, -1.4000000000000001, -1.3400000000000003], [0.13065675915114774, -0.18922731250325164, 0.6128004802613486]),
+        ("sprott_p", [-1.4100000000000001, 0.9900000000000002, -0.6000000000000001], [0.7143187173643895, -0.24920231268431303, 0.7984470719622195]),
+        ("sprott_q", [-2.1, 2.0, 4.91], [-0.26818276994093104, -0.0035194995253272363, 0.22135149559714495]),
+        ("sprott_r", [2.2, 2.5, -3.01], [0.4758735115036037, 0.8418722989595279, 0.2566298510933468]),
+        ("sprott_s", [4.5, 5.11, 1.7], [-0.7600909149394471, 0.4041940311554296, 0.968361971109223]),
+
+    ];
+
+    fn assert_close(what: &str, got: &Coord, expected: [f64; 3], tol: f64) {
+        for (axis, g, e) in [("x", got.x, expected[0]), ("y", got.y, expected[1]), ("z", got.z, expected[2])] {
+            assert!(
+                (g - e).abs() <= tol * (1.0 + e.abs()),
+                "{what}: {axis} is {g}, expected {e}",
+            );
+        }
+    }
+
+    fn reference_for(id: &str) -> (&'static [f64; 3], &'static [f64; 3]) {
+        return REFERENCE.iter()
+            .find(|(r, _, _)| *r == id)
+            .map(|(_, d, t)| (d, t))
+            .unwrap_or_else(|| panic!("no reference values for `{id}` - add it to REFERENCE"));
+    }
+
+    #[test]
+    fn every_system_has_a_reference() {
+        let registry = Registry::builtin();
+        for i in 0..registry.len() {
+            reference_for(registry.get(i).id);
+        }
+    }
+
+    #[test]
+    fn derivatives_match_reference() {
+        let registry = Registry::builtin();
+        for i in 0..registry.len() {
+            let system = registry.get(i);
+            let (derivative, _) = reference_for(system.id);
+            let got = system.derivative(&PROBE, &system.default_params());
+            assert_close(system.id, &got, *derivative, 1e-12);
+        }
+    }
+
+    #[test]
+    fn trajectories_match_reference() {
+        let registry = Registry::builtin();
+        for i in 0..registry.len() {
+            let system = registry.get(i);
+            let (_, end) = reference_for(system.id);
+            let params = system.default_params();
+            let f = |at: &Coord| system.derivative(at, &params);
+            let mut at = START;
+            for _ in 0..REF_STEPS {
+                at = Rk4.step(&f, &at, REF_DT);
+            }
+            assert_close(system.id, &at, *end, 1e-9);
+        }
+    }
+
+    #[test]
+    fn adaptive_trajectories_match_reference() {
+        //same end points, reached with a single big adaptive step
+        let registry = Registry::builtin();
+        for id in ["lorenz", "rossler_variant2", "thomas", "chua"] {
+            let system = registry.get(registry.find(id).unwrap());
+            let (_, end) = reference_for(id);
+            let params = system.default_params();
+            let f = |at: &Coord| system.derivative(at, &params);
+            let at = DORMAND_PRINCE.step(&f, &START, REF_DT * REF_STEPS as f64);
+            assert_close(id, &at, *end, 1e-5);
+        }
+    }
+
+    #[test]
+    fn lorenz_fixed_points() {
+        let (ro, sigma, beta): (f64, f64, f64) = (28.0, 10.0, 8.0/3.0);
+        let r = (beta * (ro - 1.0)).sqrt();
+        for fixed in [Coord::ZERO, Coord { x: r, y: r, z: ro - 1.0 }, Coord { x: -r, y: -r, z: ro - 1.0 }] {
+            let d = lorenz_attractor_general(&fixed, ro, sigma, beta);
+            assert_close("lorenz fixed point", &d, [0.0; 3], 1e-12);
+        }
+    }
+
+    #[test]
+    fn lorenz_symmetry() {
+        //the lorenz equations are invariant under (x, y, z) -> (-x, -y, z)
+        let mirrored = Coord { x: -PROBE.x, y: -PROBE.y, z: PROBE.z };
+        let d = lorenz_attractor_general(&PROBE, 28.0, 10.0, 8.0/3.0);
+        let dm = lorenz_attractor_general(&mirrored, 28.0, 10.0, 8.0/3.0);
+        assert_close("lorenz symmetry", &dm, [-d.x, -d.y, d.z], 1e-12);
 
-    return rossler_attractor_general(at, dt, a, b, c);
-}
\ No newline at end of file
+        //so whole trajectories are mirrored too
+        let f = |at: &Coord| lorenz_attractor_general(at, 28.0, 10.0, 8.0/3.0);
+        let mut a = START;
+        let mut b = Coord { x: -START.x, y: -START.y, z: START.z };
+        for _ in 0..REF_STEPS {
+            a = Rk4.step(&f, &a, REF_DT);
+            b = Rk4.step(&f, &b, REF_DT);
+        }
+        assert_close("lorenz mirrored trajectory", &b, [-a.x, -a.y, a.z], 1e-9);
+    }
+
+    #[test]
+    fn rossler_fixed_points() {
+        let (a, b, c): (f64, f64, f64) = (0.2, 0.2, 5.7);
+        let disc = (c * c - 4.0 * a * b).sqrt();
+        for s in [(c + disc) / 2.0, (c - disc) / 2.0] {
+            let fixed = Coord { x: s, y: -s / a, z: s / a };
+            let d = rossler_attractor_general(&fixed, a, b, c);
+            assert_close("rossler fixed point", &d, [0.0; 3], 1e-12);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use bevy::prelude::*;
+
+use crate::chaos_equations as chaos;
+use crate::colormap::Colormap;
+use crate::keybindings::{Action, Actions};
+use crate::{ChaosEquationResource, CubeMeshMaterial, Particle, SystemRegistry};
+
+/*
+    Particle Coloring
+    every particle gets a shade in [0, 1] from the active ColorMode, which is then looked up in a colormap
+*/
+
+//how many distinct materials the colormap is cut into
+pub const PALETTE_SIZE: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorMode {
+    //everything COLOR_PARTICLE, like before
+    Uniform,
+    //magnitude of the derivative
+    Speed,
+    //the z coordinate
+    Height,
+    //time since spawning
+    Age,
+    //distance from where the centroid of the bunch it was spawned with was, back then
+    Divergence,
+    //which side of x = 0 it's on - the two wings of lorenz-like systems
+    Lobe,
+}
+
+impl ColorMode {
+    pub const ALL: [ColorMode; 6] = [
+        ColorMode::Uniform,
+        ColorMode::Speed,
+        ColorMode::Height,
+        ColorMode::Age,
+        ColorMode::Divergence,
+        ColorMode::Lobe,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            ColorMode::Uniform => "uniform",
+            ColorMode::Speed => "speed",
+            ColorMode::Height => "height",
+            ColorMode::Age => "age",
+            ColorMode::Divergence => "distance from spawn centroid",
+            ColorMode::Lobe => "lobe",
+        };
+    }
+
+    pub fn next(&self) -> Self {
+        let i = ColorMode::ALL.iter().position(|m| m == self).unwrap_or(0);
+        return ColorMode::ALL[(i + 1) % ColorMode::ALL.len()];
+    }
+}
+
+#[derive(Resource)]
+pub struct ColorSettings {
+    pub mode: ColorMode,
+    pub colormap: Colormap,
+}
+
+impl Default for ColorSettings {
+    fn default() -> Self {
+        return ColorSettings {
+            mode: ColorMode::Uniform,
+            colormap: Colormap::Viridis,
+        };
+    }
+}
+
+//one material per colormap bin, recolored in place when the colormap changes
+#[derive(Resource)]
+pub struct ShadePalette(pub Vec<Handle<StandardMaterial>>);
+
+impl FromWorld for ShadePalette {
+    fn from_world(world: &mut World) -> Self {
+        let colormap = world.resource::<ColorSettings>().colormap;
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let handles = (0..PALETTE_SIZE)
+            .map(|i| materials.add(palette_color(colormap, i)))
+            .collect();
+        return ShadePalette(handles);
+    }
+}
+
+fn palette_color(colormap: Colormap, bin: usize) -> Color {
+    let [r, g, b] = colormap.sample(bin as f32 / (PALETTE_SIZE - 1) as f32);
+    return Color::srgb(r, g, b);
+}
+
+/*
+    Components
+*/
+
+//when a particle was spawned and where the bunch it came in with started out
+#[derive(Component)]
+pub struct SpawnInfo {
+    pub time: f32,
+    //centroid of the bunch when it was spawned
+    pub origin: chaos::Coord,
+}
+
+//where the particle sits on the colormap, 0.0 to 1.0
+#[derive(Component, Default)]
+pub struct Shade(pub f32);
+
+/*
+    Systems
+*/
+
+//everything spawned in the same frame counts as one bunch
+//(tagged the first frame they show up in, at most a few steps in - too soon to have spread apart)
+fn tag_new_particles(
+    mut cmd: Commands,
+    time: Res<Time>,
+    new_particles: Query<(Entity, &Particle), Without<SpawnInfo>>,
+) {
+    if new_particles.is_empty() {
+        return;
+    }
+    let mut sum = chaos::Coord::ZERO;
+    for (_, particle) in &new_particles {
+        sum = sum + particle.0;
+    }
+    let origin = sum * (1.0 / new_particles.iter().len() as f64);
+    for (entity, _) in &new_particles {
+        cmd.entity(entity).insert((
+            SpawnInfo { time: time.elapsed_seconds(), origin },
+            Shade::default(),
+        ));
+    }
+}
+
+fn shade_particles(
+    settings: Res<ColorSettings>,
+    time: Res<Time>,
+    eq: Res<ChaosEquationResource>,
+    registry: Res<SystemRegistry>,
+    mut particles: Query<(&Particle, &SpawnInfo, &mut Shade)>,
+) {
+    let system = registry.0.get(eq.system);
+    let now = time.elapsed_seconds();
+
+    //raw value of every particle first, then normalise over whatever range they span right now
+    let raw = |particle: &Particle, info: &SpawnInfo| -> f64 {
+        let c = &particle.0;
+        return match settings.mode {
+            ColorMode::Uniform => 0.0,
+            ColorMode::Speed => {
+                let d = system.derivative(c, &eq.params);
+                (d.x * d.x + d.y * d.y + d.z * d.z).sqrt()
+            }
+            ColorMode::Height => c.z,
+            ColorMode::Age => (now - info.time) as f64,
+            ColorMode::Divergence => {
+                let d = *c - info.origin;
+                (d.x * d.x + d.y * d.y + d.z * d.z).sqrt()
+            }
+            ColorMode::Lobe => if c.x < 0.0 { 0.0 } else { 1.0 },
+        };
+    };
+
+    let mut min = f64::INFINITY;
+    let mut max = f64::NEG_INFINITY;
+    for (particle, info, _) in &particles {
+        let v = raw(particle, info);
+        if v.is_finite() {
+            min = min.min(v);
+            max = max.max(v);
+        }
+    }
+    if settings.mode == ColorMode::Lobe {
+        (min, max) = (0.0, 1.0);
+    }
+    let range = if max > min { max - min } else { 1.0 };
+
+    for (particle, info, mut shade) in &mut particles {
+        shade.0 = ((raw(particle, info) - min) / range) as f32;
+    }
+}
+
+fn apply_shade_materials(
+    settings: Res<ColorSettings>,
+    palette: Res<ShadePalette>,
+    uniform: Res<CubeMeshMaterial>,
+    mut particles: Query<(&Shade, &mut Handle<StandardMaterial>), With<Particle>>,
+) {
+    for (shade, mut material) in &mut particles {
+        let wanted = if settings.mode == ColorMode::Uniform {
+            &uniform.1
+        } else {
+            let bin = (shade.0.clamp(0.0, 1.0) * (PALETTE_SIZE - 1) as f32).round() as usize;
+            &palette.0[bin]
+        };
+        if *material != *wanted {
+            *material = wanted.clone();
+        }
+    }
+}
+
+fn recolor_palette(
+    settings: Res<ColorSettings>,
+    palette: Res<ShadePalette>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (i, handle) in palette.0.iter().enumerate() {
+        if let Some(material) = materials.get_mut(handle) {
+            material.base_color = palette_color(settings.colormap, i);
+        }
+    }
+}
+
+//k cycles what the color means, m cycles the colormap
+fn coloring_keybinds(actions: Actions, mut settings: ResMut<ColorSettings>) {
+    if actions.just_pressed(Action::ColorMode) {
+        settings.mode = settings.mode.next();
+    }
+    if actions.just_pressed(Action::Colormap) {
+        settings.colormap = settings.colormap.next();
+    }
+}
+
+pub struct ColoringPlugin;
+
+impl Plugin for ColoringPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<ColorSettings>()
+            .init_resource::<ShadePalette>()
+            .add_systems(Update, coloring_keybinds)
+            .add_systems(Update, tag_new_particles)
+            .add_systems(Update, (shade_particles, apply_shade_materials).chain())
+            .add_systems(Update, recolor_palette);
+    }
+}
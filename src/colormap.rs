@@ -0,0 +1,78 @@
+/*
+    Colormaps
+    perceptually uniform gradients for mapping a value in [0, 1] to a color
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Colormap {
+    Viridis,
+    Magma,
+    Turbo,
+}
+
+//matplotlib's 10-step viridis and magma, interpolated linearly in between
+const VIRIDIS: [u32; 10] = [
+    0x440154, 0x482878, 0x3e4989, 0x31688e, 0x26828e,
+    0x1f9e89, 0x35b779, 0x6ece58, 0xb5de2b, 0xfde725,
+];
+
+const MAGMA: [u32; 10] = [
+    0x000004, 0x180f3e, 0x451077, 0x721f81, 0x9f2f7f,
+    0xcd4071, 0xf1605d, 0xfd9567, 0xfec98d, 0xfcfdbf,
+];
+
+impl Colormap {
+    pub const ALL: [Colormap; 3] = [Colormap::Viridis, Colormap::Magma, Colormap::Turbo];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Colormap::Viridis => "viridis",
+            Colormap::Magma => "magma",
+            Colormap::Turbo => "turbo",
+        };
+    }
+
+    pub fn next(&self) -> Self {
+        let i = Colormap::ALL.iter().position(|c| c == self).unwrap_or(0);
+        return Colormap::ALL[(i + 1) % Colormap::ALL.len()];
+    }
+
+    //srgb color, each channel 0.0 to 1.0; t gets clamped to [0, 1]
+    pub fn sample(&self, t: f32) -> [f32; 3] {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        return match self {
+            Colormap::Viridis => sample_table(&VIRIDIS, t),
+            Colormap::Magma => sample_table(&MAGMA, t),
+            Colormap::Turbo => turbo(t),
+        };
+    }
+}
+
+fn unpack(rgb: u32) -> [f32; 3] {
+    return [
+        ((rgb >> 16) & 0xff) as f32 / 255.0,
+        ((rgb >> 8) & 0xff) as f32 / 255.0,
+        (rgb & 0xff) as f32 / 255.0,
+    ];
+}
+
+fn sample_table(table: &[u32], t: f32) -> [f32; 3] {
+    let pos = t * (table.len() - 1) as f32;
+    let i = (pos.floor() as usize).min(table.len() - 2);
+    let frac = pos - i as f32;
+    let (a, b) = (unpack(table[i]), unpack(table[i + 1]));
+    return [
+        a[0] + (b[0] - a[0]) * frac,
+        a[1] + (b[1] - a[1]) * frac,
+        a[2] + (b[2] - a[2]) * frac,
+    ];
+}
+
+//google's polynomial approximation of turbo
+fn turbo(t: f32) -> [f32; 3] {
+    let t = t as f64;
+    let r = 0.13572138 + t * (4.61539260 + t * (-42.66032258 + t * (132.13108234 + t * (-152.94239396 + t * 59.28637943))));
+    let g = 0.09140261 + t * (2.19418839 + t * (4.84296658 + t * (-14.18503333 + t * (4.27729857 + t * 2.82956604))));
+    let b = 0.10667330 + t * (12.64194608 + t * (-60.58204836 + t * (110.36276771 + t * (-89.90310912 + t * 27.34824973))));
+    return [r.clamp(0.0, 1.0) as f32, g.clamp(0.0, 1.0) as f32, b.clamp(0.0, 1.0) as f32];
+}
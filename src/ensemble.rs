@@ -0,0 +1,206 @@
+use std::thread;
+
+use crate::chaos_equations::Coord;
+use crate::integrators::Integrator;
+use crate::lanes::{F64x4, LANES};
+use crate::registry::ChaosSystem;
+
+/*
+    Ensemble
+    a whole bunch of particles stored as structure-of-arrays - every x next to each other, same for y and z
+    stepping goes four particles at a time through the F64x4 version of the equations
+*/
+
+#[derive(Clone, Default)]
+pub struct Ensemble {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub z: Vec<f64>,
+}
+
+impl Ensemble {
+    pub fn new() -> Self {
+        return Ensemble::default();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.x.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.x.is_empty();
+    }
+
+    pub fn clear(&mut self) {
+        self.x.clear();
+        self.y.clear();
+        self.z.clear();
+    }
+
+    pub fn push(&mut self, c: Coord) {
+        self.x.push(c.x);
+        self.y.push(c.y);
+        self.z.push(c.z);
+    }
+
+    pub fn get(&self, i: usize) -> Coord {
+        return Coord { x: self.x[i], y: self.y[i], z: self.z[i] };
+    }
+
+    pub fn set(&mut self, i: usize, c: Coord) {
+        self.x[i] = c.x;
+        self.y[i] = c.y;
+        self.z[i] = c.z;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Coord> + '_ {
+        return (0..self.len()).map(|i| self.get(i));
+    }
+
+    pub fn as_mut(&mut self) -> EnsembleMut<'_> {
+        return EnsembleMut { x: &mut self.x, y: &mut self.y, z: &mut self.z };
+    }
+
+    //disjoint pieces of at most `size` particles each, to hand to different threads
+    //`size` gets rounded up to whole lanes so only the last piece has a scalar tail
+    pub fn chunks_mut(&mut self, size: usize) -> impl Iterator<Item = EnsembleMut<'_>> {
+        let size = size.max(1).next_multiple_of(LANES);
+        return self.x.chunks_mut(size)
+            .zip(self.y.chunks_mut(size))
+            .zip(self.z.chunks_mut(size))
+            .map(|((x, y), z)| EnsembleMut { x, y, z });
+    }
+
+    //`steps` steps of `dt` for every particle, on this thread
+    pub fn step(&mut self, system: &ChaosSystem, params: &[f64], integrator: &dyn Integrator, dt: f64, steps: usize) {
+        self.as_mut().step(system, params, integrator, dt, steps);
+    }
+
+    //same as step, split evenly over `threads` threads
+    pub fn step_parallel(&mut self, threads: usize, system: &ChaosSystem, params: &[f64], integrator: &dyn Integrator, dt: f64, steps: usize) {
+        let size = self.len().div_ceil(threads.max(1));
+        thread::scope(|scope| {
+            for mut chunk in self.chunks_mut(size) {
+                scope.spawn(move || chunk.step(system, params, integrator, dt, steps));
+            }
+        });
+    }
+}
+
+impl FromIterator<Coord> for Ensemble {
+    fn from_iter<I: IntoIterator<Item = Coord>>(iter: I) -> Self {
+        let mut ensemble = Ensemble::new();
+        for c in iter {
+            ensemble.push(c);
+        }
+        return ensemble;
+    }
+}
+
+//a mutable window into an ensemble - all of it, or one of its chunks
+pub struct EnsembleMut<'a> {
+    pub x: &'a mut [f64],
+    pub y: &'a mut [f64],
+    pub z: &'a mut [f64],
+}
+
+impl EnsembleMut<'_> {
+    pub fn len(&self) -> usize {
+        return self.x.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.x.is_empty();
+    }
+
+    pub fn step(&mut self, system: &ChaosSystem, params: &[f64], integrator: &dyn Integrator, dt: f64, steps: usize) {
+        let f = |at: &Coord| system.derivative(at, params);
+        let f_x4 = |at: &Coord<F64x4>| system.derivative_x4(at, params);
+        let lanes_end = self.len() - self.len() % LANES;
+
+        for i in (0..lanes_end).step_by(LANES) {
+            let range = i..i + LANES;
+            let mut at = Coord {
+                x: F64x4::new(self.x[range.clone()].try_into().unwrap()),
+                y: F64x4::new(self.y[range.clone()].try_into().unwrap()),
+                z: F64x4::new(self.z[range.clone()].try_into().unwrap()),
+            };
+            for _ in 0..steps {
+                at = integrator.step_x4(&f, &f_x4, &at, dt);
+            }
+            self.x[range.clone()].copy_from_slice(&at.x.to_array());
+            self.y[range.clone()].copy_from_slice(&at.y.to_array());
+            self.z[range].copy_from_slice(&at.z.to_array());
+        }
+
+        //whatever doesn't fill a whole F64x4
+        for i in lanes_end..self.len() {
+            let mut at = Coord { x: self.x[i], y: self.y[i], z: self.z[i] };
+            for _ in 0..steps {
+                at = integrator.step(&f, &at, dt);
+            }
+            (self.x[i], self.y[i], self.z[i]) = (at.x, at.y, at.z);
+        }
+    }
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrators::INTEGRATORS;
+    use crate::registry::Registry;
+
+    //an odd count, so both the lanes and the scalar tail get exercised
+    const PARTICLES: usize = 11;
+    const STEPS: usize = 200;
+    const DT: f64 = 0.002;
+
+    fn start() -> Ensemble {
+        return (0..PARTICLES)
+            .map(|i| Coord { x: 0.1 + i as f64 * 0.01, y: 0.2 - i as f64 * 0.01, z: 0.3 })
+            .collect();
+    }
+
+    #[test]
+    fn lanes_match_scalar_stepping() {
+        let registry = Registry::builtin();
+        for system in registry.iter() {
+            let params = system.default_params();
+            let f = |at: &Coord| system.derivative(at, &params);
+            for integrator in INTEGRATORS.iter().take(3) {
+                let mut ensemble = start();
+                ensemble.step(system, &params, *integrator, DT, STEPS);
+                for (i, got) in ensemble.iter().enumerate() {
+                    let mut at = start().get(i);
+                    for _ in 0..STEPS {
+                        at = integrator.step(&f, &at, DT);
+                    }
+                    //same operations in the same order, so not even rounding should differ
+                    assert!(
+                        got.x == at.x && got.y == at.y && got.z == at.z,
+                        "{} with {}: particle {i} diverged from scalar stepping", system.id, integrator.name(),
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_matches_serial() {
+        let registry = Registry::builtin();
+        let system = registry.get(registry.find("lorenz").unwrap());
+        let params = system.default_params();
+        let integrator = INTEGRATORS[2];
+        let mut serial = start();
+        serial.step(system, &params, integrator, DT, STEPS);
+        let mut parallel = start();
+        parallel.step_parallel(3, system, &params, integrator, DT, STEPS);
+        assert_eq!(serial.x, parallel.x);
+        assert_eq!(serial.y, parallel.y);
+        assert_eq!(serial.z, parallel.z);
+    }
+}
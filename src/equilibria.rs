@@ -0,0 +1,161 @@
+use bevy::color::palettes::css::{GRAY, LIME, ORANGE, RED};
+use bevy::prelude::*;
+
+use chaos_core::fixed_points::{self, Eigenvalue, FixedPoint, Stability};
+
+use crate::keybindings::{Action, Actions};
+use crate::settings::SimSettings;
+use crate::{draw_axes, ChaosEquationResource, SystemRegistry};
+
+/*
+    Equilibria
+    the fixed points of the current system and parameters (see chaos_core::fixed_points), marked in the scene
+    with a sphere colored by what they do to the flow, and listed with their eigenvalues in the top right corner
+    j hides or shows them; they're found again whenever the system or a parameter changes
+*/
+
+//marker radius, as a fraction of the attractor's size
+pub const MARKER_SIZE: f64 = 0.02;
+
+#[derive(Resource)]
+pub struct Equilibria {
+    pub enabled: bool,
+    pub points: Vec<FixedPoint>,
+    //the system and parameters the points are for
+    key: Option<(usize, Vec<f64>)>,
+}
+
+impl Default for Equilibria {
+    fn default() -> Self {
+        return Equilibria { enabled: true, points: Vec::new(), key: None };
+    }
+}
+
+impl Equilibria {
+    //"1 saddle, 2 saddle-focus"
+    pub fn summary(&self) -> String {
+        let mut counts: Vec<(&'static str, usize)> = Vec::new();
+        for point in &self.points {
+            let name = point.stability.name();
+            match counts.iter_mut().find(|c| c.0 == name) {
+                Some(c) => c.1 += 1,
+                None => counts.push((name, 1)),
+            }
+        }
+        if counts.is_empty() {
+            return "none".to_string();
+        }
+        return counts.iter().map(|(name, n)| format!("{n} {name}")).collect::<Vec<_>>().join(", ");
+    }
+}
+
+#[derive(Component)]
+struct EquilibriaText;
+
+fn find_equilibria(mut equilibria: ResMut<Equilibria>, eq: Res<ChaosEquationResource>, registry: Res<SystemRegistry>) {
+    let key = (eq.system, eq.params.clone());
+    if equilibria.key.as_ref() == Some(&key) {
+        return;
+    }
+    equilibria.points = fixed_points::find(registry.0.get(eq.system), &eq.params);
+    equilibria.key = Some(key);
+}
+
+fn equilibria_keybinds(actions: Actions, mut equilibria: ResMut<Equilibria>) {
+    if actions.just_pressed(Action::FixedPoints) {
+        equilibria.enabled = !equilibria.enabled;
+    }
+}
+
+//attracting green, repelling red, saddles in between
+fn color(stability: Stability) -> Srgba {
+    return match stability {
+        Stability::Sink | Stability::SpiralSink => LIME,
+        Stability::Source | Stability::SpiralSource => RED,
+        Stability::Saddle { .. } | Stability::SaddleFocus { .. } => ORANGE,
+        Stability::NonHyperbolic => GRAY,
+    };
+}
+
+fn draw_equilibria(
+    mut gizmos: Gizmos,
+    equilibria: Res<Equilibria>,
+    eq: Res<ChaosEquationResource>,
+    registry: Res<SystemRegistry>,
+    sim_settings: Res<SimSettings>,
+) {
+    if !equilibria.enabled {
+        return;
+    }
+    let radius = (registry.0.get(eq.system).framing.radius * MARKER_SIZE * sim_settings.virt_zoom) as f32;
+    for point in &equilibria.points {
+        gizmos.sphere(sim_settings.virt_to_world(&point.at).translation, Quat::IDENTITY, radius, color(point.stability));
+    }
+}
+
+//newton lands a hair off 0, which shouldn't show up as -0.000
+fn tidy(v: f64) -> f64 {
+    return if v.abs() < 5e-4 { 0.0 } else { v };
+}
+
+//"-1.2" or "-1.2±3.4i"
+fn format_eigenvalue(e: &Eigenvalue) -> String {
+    if e.1 == 0.0 {
+        return format!("{:.3}", e.0);
+    }
+    return format!("{:.3}±{:.3}i", e.0, e.1.abs());
+}
+
+fn init_equilibria_text(mut cmd: Commands) {
+    cmd.spawn((EquilibriaText, TextBundle {
+        text: Text::from_section("", TextStyle::default()),
+        style: Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            right: Val::Px(5.0),
+            ..default()
+        },
+        ..default()
+    }));
+}
+
+//one line per point: where it is, what kind, and its eigenvalues (a complex pair only once)
+fn display_equilibria(equilibria: Res<Equilibria>, mut q_text: Query<&mut Text, With<EquilibriaText>>) {
+    if !equilibria.is_changed() {
+        return;
+    }
+    let Ok(mut text) = q_text.get_single_mut() else {
+        return;
+    };
+    if !equilibria.enabled {
+        text.sections[0].value.clear();
+        return;
+    }
+    text.sections[0].value = equilibria.points.iter()
+        .map(|p| {
+            let eigenvalues: Vec<String> = p.eigenvalues.iter()
+                .filter(|e| e.1 >= 0.0)
+                .map(format_eigenvalue)
+                .collect();
+            return format!(
+                "({:.3}, {:.3}, {:.3}) {}: {}",
+                tidy(p.at.x), tidy(p.at.y), tidy(p.at.z), p.stability.name(), eigenvalues.join(", "),
+            );
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+}
+
+pub struct EquilibriaPlugin;
+
+impl Plugin for EquilibriaPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Equilibria>()
+            .add_systems(Startup, init_equilibria_text)
+            .add_systems(Update, find_equilibria)
+            .add_systems(Update, equilibria_keybinds)
+            .add_systems(Update, draw_equilibria.after(draw_axes).after(find_equilibria))
+            .add_systems(Update, display_equilibria.after(find_equilibria).after(equilibria_keybinds));
+    }
+}
@@ -0,0 +1,454 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use parquet::data_type::{DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::format::KeyValue;
+use parquet::schema::parser::parse_message_type;
+
+use crate::chaos_equations::Coord;
+use crate::integrators::Integrator;
+use crate::registry::ChaosSystem;
+
+/*
+    Trajectory Export
+    every format gets the same rows - step, t, particle, x, y, z - plus what produced them (system, parameters, dt, integrator)
+    rows are streamed as they come, so a recording can run for as long as there's disk
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    //plain text, the run info in `#` comment lines on top (`pandas.read_csv(path, comment='#')`)
+    Csv,
+    //numpy structured array (`numpy.load(path)`), the run info in a .json next to it
+    Npy,
+    //one column per field, the run info in the file's key-value metadata
+    Parquet,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Csv, Format::Npy, Format::Parquet];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Format::Csv => "csv",
+            Format::Npy => "npy",
+            Format::Parquet => "parquet",
+        };
+    }
+
+    pub fn extension(&self) -> &'static str {
+        return self.name();
+    }
+
+    pub fn next(&self) -> Self {
+        let i = Format::ALL.iter().position(|f| f == self).unwrap_or(0);
+        return Format::ALL[(i + 1) % Format::ALL.len()];
+    }
+
+    //guessed from the file extension
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        return Format::ALL.into_iter().find(|f| f.extension() == ext);
+    }
+}
+
+//what a trajectory came from
+#[derive(Clone)]
+pub struct RunInfo {
+    pub system: String,
+    pub params: Vec<(String, f64)>,
+    pub dt: f64,
+    pub integrator: String,
+    //for poincaré sections, which plane the rows are crossings of
+    pub section: Option<String>,
+}
+
+impl RunInfo {
+    pub fn new(system: &ChaosSystem, params: &[f64], dt: f64, integrator: &dyn Integrator) -> Self {
+        return RunInfo {
+            system: system.id.to_string(),
+            params: system.params.iter().zip(params).map(|(p, v)| (p.name.to_string(), *v)).collect(),
+            dt,
+            integrator: integrator.name().to_string(),
+            section: None,
+        };
+    }
+
+    //as text key-value pairs, in the order they get written
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let params: Vec<String> = self.params.iter().map(|(name, v)| format!("{name}={v}")).collect();
+        let mut entries = vec![
+            ("system", self.system.clone()),
+            ("params", params.join(" ")),
+            ("dt", self.dt.to_string()),
+            ("integrator", self.integrator.clone()),
+        ];
+        if let Some(section) = &self.section {
+            entries.push(("section", section.clone()));
+        }
+        return entries;
+    }
+}
+
+//one particle at one point in time
+#[derive(Clone, Copy)]
+pub struct Row {
+    pub step: u64,
+    pub t: f64,
+    pub particle: u64,
+    pub at: Coord,
+}
+
+pub trait TrajectoryWriter {
+    fn write(&mut self, row: &Row) -> io::Result<()>;
+    //flush whatever is buffered and complete the file - without this npy and parquet files aren't readable
+    //nothing may be written after it
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+//a writer for `path`; the file (and for npy the .json next to it) gets created right away
+pub fn create(path: &Path, format: Format, info: &RunInfo) -> io::Result<Box<dyn TrajectoryWriter + Send>> {
+    let file = File::create(path)?;
+    return Ok(match format {
+        Format::Csv => Box::new(CsvWriter::new(BufWriter::new(file), info)?),
+        Format::Npy => {
+            write_json_sidecar(&path.with_extension("json"), info)?;
+            Box::new(NpyWriter::new(BufWriter::new(file))?)
+        }
+        Format::Parquet => Box::new(ParquetWriter::new(file, info)?),
+    });
+}
+
+/*
+    CSV
+*/
+
+pub struct CsvWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(mut out: W, info: &RunInfo) -> io::Result<Self> {
+        for (key, value) in info.entries() {
+            writeln!(out, "# {key}: {value}")?;
+        }
+        writeln!(out, "step,t,particle,x,y,z")?;
+        return Ok(CsvWriter { out });
+    }
+}
+
+impl<W: Write> TrajectoryWriter for CsvWriter<W> {
+    fn write(&mut self, row: &Row) -> io::Result<()> {
+        return writeln!(self.out, "{},{},{},{},{},{}", row.step, row.t, row.particle, row.at.x, row.at.y, row.at.z);
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        return self.out.flush();
+    }
+}
+
+/*
+    NPY
+    version 1.0 of the format - magic, header length, a python dict literal describing the array, then raw little endian rows
+    the row count isn't known until the end, so the header leaves room for the biggest one and gets rewritten in finish
+*/
+
+const NPY_DESCR: &str = "[('step', '<u8'), ('t', '<f8'), ('particle', '<u8'), ('x', '<f8'), ('y', '<f8'), ('z', '<f8')]";
+
+pub struct NpyWriter<W: Write + Seek> {
+    out: W,
+    rows: u64,
+}
+
+impl<W: Write + Seek> NpyWriter<W> {
+    pub fn new(mut out: W) -> io::Result<Self> {
+        out.write_all(&npy_header(0))?;
+        return Ok(NpyWriter { out, rows: 0 });
+    }
+}
+
+//always the same length, whatever the row count
+fn npy_header(rows: u64) -> Vec<u8> {
+    let dict = format!("{{'descr': {NPY_DESCR}, 'fortran_order': False, 'shape': ({rows},), }}");
+    let widest = format!("{{'descr': {NPY_DESCR}, 'fortran_order': False, 'shape': ({},), }}", u64::MAX);
+    //magic + version + length take 10 bytes, and everything before the data has to be a multiple of 64 long
+    let total = (10 + widest.len() + 1).next_multiple_of(64);
+    let mut header = b"\x93NUMPY\x01\x00".to_vec();
+    header.extend_from_slice(&((total - 10) as u16).to_le_bytes());
+    header.extend_from_slice(dict.as_bytes());
+    header.resize(total - 1, b' ');
+    header.push(b'\n');
+    return header;
+}
+
+impl<W: Write + Seek> TrajectoryWriter for NpyWriter<W> {
+    fn write(&mut self, row: &Row) -> io::Result<()> {
+        let mut bytes = [0u8; 48];
+        bytes[0..8].copy_from_slice(&row.step.to_le_bytes());
+        bytes[8..16].copy_from_slice(&row.t.to_le_bytes());
+        bytes[16..24].copy_from_slice(&row.particle.to_le_bytes());
+        bytes[24..32].copy_from_slice(&row.at.x.to_le_bytes());
+        bytes[32..40].copy_from_slice(&row.at.y.to_le_bytes());
+        bytes[40..48].copy_from_slice(&row.at.z.to_le_bytes());
+        self.rows += 1;
+        return self.out.write_all(&bytes);
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let end = self.out.stream_position()?;
+        self.out.seek(SeekFrom::Start(0))?;
+        self.out.write_all(&npy_header(self.rows))?;
+        self.out.seek(SeekFrom::Start(end))?;
+        return self.out.flush();
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    return out;
+}
+
+//json has no inf or nan
+fn json_number(v: f64) -> String {
+    return if v.is_finite() { v.to_string() } else { "null".to_string() };
+}
+
+pub fn write_json_sidecar(path: &Path, info: &RunInfo) -> io::Result<()> {
+    return std::fs::write(path, sidecar_json(info));
+}
+
+//same fields as RunInfo::entries, but with the params as an object of numbers
+fn sidecar_json(info: &RunInfo) -> String {
+    let params: Vec<String> = info.params.iter()
+        .map(|(name, v)| format!("{}: {}", json_string(name), json_number(*v)))
+        .collect();
+    let mut fields = vec![
+        format!("\"system\": {}", json_string(&info.system)),
+        format!("\"params\": {{{}}}", params.join(", ")),
+        format!("\"dt\": {}", json_number(info.dt)),
+        format!("\"integrator\": {}", json_string(&info.integrator)),
+    ];
+    if let Some(section) = &info.section {
+        fields.push(format!("\"section\": {}", json_string(section)));
+    }
+    return format!("{{\n  {}\n}}\n", fields.join(",\n  "));
+}
+
+/*
+    Parquet
+    rows are collected column by column and written out as a row group whenever enough have piled up
+*/
+
+const PARQUET_SCHEMA: &str = "
+    message trajectory {
+        required int64 step;
+        required double t;
+        required int64 particle;
+        required double x;
+        required double y;
+        required double z;
+    }
+";
+
+const ROW_GROUP_ROWS: usize = 1 << 16;
+
+#[derive(Default)]
+struct Columns {
+    step: Vec<i64>,
+    t: Vec<f64>,
+    particle: Vec<i64>,
+    x: Vec<f64>,
+    y: Vec<f64>,
+    z: Vec<f64>,
+}
+
+pub struct ParquetWriter<W: Write + Send> {
+    //None once finished
+    out: Option<SerializedFileWriter<W>>,
+    columns: Columns,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    pub fn new(out: W, info: &RunInfo) -> io::Result<Self> {
+        let schema = Arc::new(parse_message_type(PARQUET_SCHEMA).map_err(io::Error::other)?);
+        let metadata = info.entries().into_iter()
+            .map(|(key, value)| KeyValue::new(key.to_string(), value))
+            .collect();
+        let props = Arc::new(WriterProperties::builder().set_key_value_metadata(Some(metadata)).build());
+        let out = SerializedFileWriter::new(out, schema, props).map_err(io::Error::other)?;
+        return Ok(ParquetWriter { out: Some(out), columns: Columns::default() });
+    }
+
+    fn flush_row_group(&mut self) -> parquet::errors::Result<()> {
+        let Some(out) = self.out.as_mut() else {
+            return Ok(());
+        };
+        if self.columns.step.is_empty() {
+            return Ok(());
+        }
+        let c = std::mem::take(&mut self.columns);
+        let mut group = out.next_row_group()?;
+        let mut i = 0;
+        while let Some(mut column) = group.next_column()? {
+            match i {
+                0 => column.typed::<Int64Type>().write_batch(&c.step, None, None)?,
+                1 => column.typed::<DoubleType>().write_batch(&c.t, None, None)?,
+                2 => column.typed::<Int64Type>().write_batch(&c.particle, None, None)?,
+                3 => column.typed::<DoubleType>().write_batch(&c.x, None, None)?,
+                4 => column.typed::<DoubleType>().write_batch(&c.y, None, None)?,
+                _ => column.typed::<DoubleType>().write_batch(&c.z, None, None)?,
+            };
+            column.close()?;
+            i += 1;
+        }
+        group.close()?;
+        return Ok(());
+    }
+}
+
+impl<W: Write + Send> TrajectoryWriter for ParquetWriter<W> {
+    fn write(&mut self, row: &Row) -> io::Result<()> {
+        let c = &mut self.columns;
+        c.step.push(row.step as i64);
+        c.t.push(row.t);
+        c.particle.push(row.particle as i64);
+        c.x.push(row.at.x);
+        c.y.push(row.at.y);
+        c.z.push(row.at.z);
+        if c.step.len() >= ROW_GROUP_ROWS {
+            self.flush_row_group().map_err(io::Error::other)?;
+        }
+        return Ok(());
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.flush_row_group().map_err(io::Error::other)?;
+        if let Some(out) = self.out.take() {
+            out.close().map_err(io::Error::other)?;
+        }
+        return Ok(());
+    }
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+
+    use crate::registry::Registry;
+
+    fn info() -> RunInfo {
+        let registry = Registry::builtin();
+        let system = registry.get(registry.find("lorenz").unwrap());
+        return RunInfo::new(system, &system.default_params(), 0.005, crate::integrators::INTEGRATORS[2]);
+    }
+
+    fn rows() -> Vec<Row> {
+        return (0..5)
+            .map(|i| Row {
+                step: i / 2,
+                t: (i / 2) as f64 * 0.005,
+                particle: i % 2,
+                at: Coord { x: i as f64, y: -(i as f64), z: 0.5 },
+            })
+            .collect();
+    }
+
+    #[test]
+    fn csv_has_run_info_and_rows() {
+        let mut buf = Vec::new();
+        let mut writer = CsvWriter::new(&mut buf, &info()).unwrap();
+        for row in rows() {
+            writer.write(&row).unwrap();
+        }
+        writer.finish().unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "# system: lorenz");
+        assert!(lines[1].starts_with("# params: rho=28 sigma=10 beta="));
+        assert_eq!(lines[4], "step,t,particle,x,y,z");
+        assert_eq!(lines[5..].len(), 5);
+        assert_eq!(lines[8], "1,0.005,1,3,-3,0.5");
+    }
+
+    #[test]
+    fn npy_header_gets_the_final_row_count() {
+        let mut writer = NpyWriter::new(Cursor::new(Vec::new())).unwrap();
+        for row in rows() {
+            writer.write(&row).unwrap();
+        }
+        writer.finish().unwrap();
+        let bytes = writer.out.into_inner();
+
+        assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = std::str::from_utf8(&bytes[10..10 + header_len]).unwrap();
+        assert!(header.contains("'shape': (5,)"), "{header}");
+        assert!(header.ends_with('\n'));
+
+        let data = &bytes[10 + header_len..];
+        assert_eq!(data.len(), 5 * 48);
+        //fourth row: step 1, particle 1, x 3
+        let row = &data[3 * 48..4 * 48];
+        assert_eq!(u64::from_le_bytes(row[0..8].try_into().unwrap()), 1);
+        assert_eq!(u64::from_le_bytes(row[16..24].try_into().unwrap()), 1);
+        assert_eq!(f64::from_le_bytes(row[24..32].try_into().unwrap()), 3.0);
+    }
+
+    #[test]
+    fn json_sidecar_has_run_info() {
+        let mut info = info();
+        let json = sidecar_json(&info);
+        assert!(json.starts_with("{\n  \"system\": \"lorenz\",\n  \"params\": {\"rho\": 28, \"sigma\": 10, \"beta\": "), "{json}");
+        assert!(json.ends_with("\"dt\": 0.005,\n  \"integrator\": \"RK4\"\n}\n"), "{json}");
+        assert!(!json.contains("section"));
+
+        info.section = Some("z = 27".to_string());
+        let json = sidecar_json(&info);
+        assert!(json.ends_with("\"integrator\": \"RK4\",\n  \"section\": \"z = 27\"\n}\n"), "{json}");
+    }
+
+    #[test]
+    fn parquet_reads_back() {
+        let path = std::env::temp_dir().join(format!("chaos-export-test-{}.parquet", std::process::id()));
+        let mut writer = create(&path, Format::Parquet, &info()).unwrap();
+        for row in rows() {
+            writer.write(&row).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 5);
+        let kv = metadata.key_value_metadata().unwrap();
+        assert!(kv.iter().any(|e| e.key == "system" && e.value.as_deref() == Some("lorenz")));
+
+        let row = reader.get_row_iter(None).unwrap().nth(3).unwrap().unwrap();
+        let fields: Vec<&Field> = row.get_column_iter().map(|(_, f)| f).collect();
+        assert_eq!(fields[0], &Field::Long(1));
+        assert_eq!(fields[2], &Field::Long(1));
+        assert_eq!(fields[3], &Field::Double(3.0));
+    }
+}
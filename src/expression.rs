@@ -0,0 +1,645 @@
+use std::fmt;
+
+use crate::chaos_equations::Coord;
+use crate::lanes::{F64x4, LANES};
+
+/*
+    Expressions
+    the little language for systems typed in instead of written in rust:
+
+        dx = s*(y - x)
+        dy = x*(r - z) - y
+        dz = x*y - b*z
+        s = 10; r = 28; b = 8/3
+
+    one assignment per line or per `;`, `#` comments out the rest of a line
+    dx, dy and dz are the equations, in x, y, z and the parameters; every other name that gets assigned is a parameter,
+    and what it's assigned (numbers only) is its default
+    there's + - * / ^, parentheses, pi and e, and the functions in FUNCTIONS
+    every equation gets compiled to a list of instructions for a small stack machine, which runs them
+    on one particle or on four side by side for the lanes version
+*/
+
+//where in the source something went wrong, both counting from 1
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "line {}, column {}: {}", self.line, self.column, self.message);
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos {
+    line: usize,
+    column: usize,
+}
+
+impl Pos {
+    fn error<T>(self, message: impl Into<String>) -> Result<T, ParseError> {
+        return Err(ParseError { line: self.line, column: self.column, message: message.into() });
+    }
+}
+
+/*
+    Tokens
+*/
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    //one of + - * / ^ ( ) , =
+    Symbol(char),
+    //a line break or `;`
+    Separator,
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Token::Number(v) => write!(f, "`{v}`"),
+            Token::Name(name) => write!(f, "`{name}`"),
+            Token::Symbol(c) => write!(f, "`{c}`"),
+            Token::Separator => write!(f, "the end of the line"),
+            Token::End => write!(f, "the end"),
+        };
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, Pos)>, ParseError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line, mut line_start) = (0, 1, 0);
+    while i < chars.len() {
+        let c = chars[i];
+        let pos = Pos { line, column: i - line_start + 1 };
+        if c == '\n' || c == ';' {
+            tokens.push((Token::Separator, pos));
+            if c == '\n' {
+                (line, line_start) = (line + 1, i + 1);
+            }
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            //an exponent, but only if digits follow
+            let sign = usize::from(i + 1 < chars.len() && matches!(chars[i + 1], '+' | '-'));
+            if i < chars.len() && matches!(chars[i], 'e' | 'E') && chars.get(i + 1 + sign).is_some_and(|c| c.is_ascii_digit()) {
+                i += 1 + sign;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            match text.parse() {
+                Ok(v) => tokens.push((Token::Number(v), pos)),
+                Err(_) => return pos.error(format!("`{text}` isn't a number")),
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Name(chars[start..i].iter().collect()), pos));
+        } else if "+-*/^(),=".contains(c) {
+            tokens.push((Token::Symbol(c), pos));
+            i += 1;
+        } else {
+            return pos.error(format!("unexpected `{c}`"));
+        }
+    }
+    tokens.push((Token::End, Pos { line, column: chars.len() - line_start + 1 }));
+    return Ok(tokens);
+}
+
+/*
+    Parsing
+*/
+
+#[derive(Clone, Copy)]
+enum Function {
+    One(fn(f64) -> f64),
+    Two(fn(f64, f64) -> f64),
+}
+
+const FUNCTIONS: &[(&str, Function)] = &[
+    ("sin", Function::One(f64::sin)),
+    ("cos", Function::One(f64::cos)),
+    ("tan", Function::One(f64::tan)),
+    ("asin", Function::One(f64::asin)),
+    ("acos", Function::One(f64::acos)),
+    ("atan", Function::One(f64::atan)),
+    ("sinh", Function::One(f64::sinh)),
+    ("cosh", Function::One(f64::cosh)),
+    ("tanh", Function::One(f64::tanh)),
+    ("exp", Function::One(f64::exp)),
+    ("ln", Function::One(f64::ln)),
+    ("sqrt", Function::One(f64::sqrt)),
+    ("abs", Function::One(f64::abs)),
+    ("sign", Function::One(f64::signum)),
+    ("floor", Function::One(f64::floor)),
+    ("min", Function::Two(f64::min)),
+    ("max", Function::Two(f64::max)),
+    ("atan2", Function::Two(f64::atan2)),
+];
+
+const CONSTANTS: &[(&str, f64)] = &[("pi", std::f64::consts::PI), ("e", std::f64::consts::E)];
+
+const COORDINATES: [&str; 3] = ["x", "y", "z"];
+const EQUATIONS: [&str; 3] = ["dx", "dy", "dz"];
+
+enum Expr {
+    Number(f64),
+    Name(String, Pos),
+    Neg(Box<Expr>),
+    //`^`, the one operator that groups right to left
+    Pow(Box<Expr>, Box<Expr>),
+    //a run of + and -, or of * and /, kept flat so a long line doesn't make a deep tree
+    Chain(Box<Expr>, Vec<(char, Expr)>),
+    Call(String, Pos, Vec<Expr>),
+}
+
+impl Expr {
+    fn chain(first: Expr, rest: Vec<(char, Expr)>) -> Expr {
+        if rest.is_empty() {
+            return first;
+        }
+        return Expr::Chain(Box::new(first), rest);
+    }
+}
+
+//how many brackets, signs and exponents deep an expression can go, so the parser can't overflow its own stack
+const MAX_NESTING: usize = 256;
+
+struct Parser {
+    tokens: Vec<(Token, Pos)>,
+    next: usize,
+    //recursion depth of `nested`
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &(Token, Pos) {
+        return &self.tokens[self.next];
+    }
+
+    fn advance(&mut self) -> (Token, Pos) {
+        let token = self.tokens[self.next].clone();
+        if token.0 != Token::End {
+            self.next += 1;
+        }
+        return token;
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek().0 == Token::Symbol(c) {
+            self.next += 1;
+            return true;
+        }
+        return false;
+    }
+
+    fn expect(&mut self, c: char, after: &str) -> Result<(), ParseError> {
+        if self.eat(c) {
+            return Ok(());
+        }
+        let (token, pos) = self.peek();
+        return pos.error(format!("expected `{c}` {after}, got {token}"));
+    }
+
+    //every recursive call goes through here
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Expr, ParseError>) -> Result<Expr, ParseError> {
+        if self.depth == MAX_NESTING {
+            return self.peek().1.error(format!("too deeply nested, more than {MAX_NESTING} levels"));
+        }
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        return expr;
+    }
+
+    //`name = expr` lines, empty ones skipped
+    fn statements(&mut self) -> Result<Vec<(String, Pos, Expr)>, ParseError> {
+        let mut statements = Vec::new();
+        loop {
+            match self.advance() {
+                (Token::Separator, _) => continue,
+                (Token::End, _) => return Ok(statements),
+                (Token::Name(name), pos) => {
+                    self.expect('=', &format!("after `{name}`"))?;
+                    let expr = self.sum()?;
+                    match self.advance() {
+                        (Token::Separator | Token::End, _) => statements.push((name, pos, expr)),
+                        (token, pos) => return pos.error(format!("expected the end of the line, got {token}")),
+                    }
+                }
+                (token, pos) => return pos.error(format!("expected a name to assign to, like `dx = ...`, got {token}")),
+            }
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, ParseError> {
+        let first = self.product()?;
+        let mut rest = Vec::new();
+        while let Token::Symbol(op @ ('+' | '-')) = self.peek().0 {
+            self.next += 1;
+            rest.push((op, self.product()?));
+        }
+        return Ok(Expr::chain(first, rest));
+    }
+
+    fn product(&mut self) -> Result<Expr, ParseError> {
+        let first = self.unary()?;
+        let mut rest = Vec::new();
+        while let Token::Symbol(op @ ('*' | '/')) = self.peek().0 {
+            self.next += 1;
+            rest.push((op, self.unary()?));
+        }
+        return Ok(Expr::chain(first, rest));
+    }
+
+    //-x^2 is -(x^2), like on paper
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.nested(Self::unary)?)));
+        }
+        if self.eat('+') {
+            return self.nested(Self::unary);
+        }
+        return self.power();
+    }
+
+    //right to left, 2^3^2 is 2^9
+    fn power(&mut self) -> Result<Expr, ParseError> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            return Ok(Expr::Pow(Box::new(base), Box::new(self.nested(Self::unary)?)));
+        }
+        return Ok(base);
+    }
+
+    fn atom(&mut self) -> Result<Expr, ParseError> {
+        return match self.advance() {
+            (Token::Number(v), _) => Ok(Expr::Number(v)),
+            (Token::Name(name), pos) => {
+                if !self.eat('(') {
+                    return Ok(Expr::Name(name, pos));
+                }
+                let mut args = vec![self.nested(Self::sum)?];
+                while self.eat(',') {
+                    args.push(self.nested(Self::sum)?);
+                }
+                self.expect(')', &format!("to close `{name}(`"))?;
+                Ok(Expr::Call(name, pos, args))
+            }
+            (Token::Symbol('('), _) => {
+                let expr = self.nested(Self::sum)?;
+                self.expect(')', "to close `(`")?;
+                Ok(expr)
+            }
+            (token, pos) => pos.error(format!("expected a number, a name or `(`, got {token}")),
+        };
+    }
+}
+
+/*
+    Compiling
+*/
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Number(f64),
+    //x, y or z
+    Coordinate(usize),
+    Param(usize),
+    Neg,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Call1(fn(f64) -> f64),
+    Call2(fn(f64, f64) -> f64),
+}
+
+//how deep the stack of the machine goes - a lot more than any equation worth typing needs
+pub const STACK_SIZE: usize = 32;
+
+fn push<const N: usize>(stack: &mut [[f64; N]; STACK_SIZE], top: &mut usize, value: [f64; N]) {
+    stack[*top] = value;
+    *top += 1;
+}
+
+//the top two values replaced with f(second from the top, top)
+fn binary<const N: usize>(stack: &mut [[f64; N]; STACK_SIZE], top: &mut usize, f: impl Fn(f64, f64) -> f64) {
+    *top -= 1;
+    let b = stack[*top];
+    for (a, b) in stack[*top - 1].iter_mut().zip(b) {
+        *a = f(*a, b);
+    }
+}
+
+//one equation, as stack machine instructions
+#[derive(Clone, Debug)]
+pub struct Program {
+    ops: Vec<Op>,
+}
+
+impl Program {
+    //the value for N particles at once, `at` being their x, y and z
+    pub fn eval<const N: usize>(&self, at: &[[f64; N]; 3], params: &[f64]) -> [f64; N] {
+        let mut stack = [[0.0; N]; STACK_SIZE];
+        let mut top = 0;
+        for op in &self.ops {
+            match *op {
+                Op::Number(v) => push(&mut stack, &mut top, [v; N]),
+                Op::Coordinate(i) => push(&mut stack, &mut top, at[i]),
+                Op::Param(i) => push(&mut stack, &mut top, [params[i]; N]),
+                Op::Neg => stack[top - 1] = stack[top - 1].map(|a| -a),
+                Op::Call1(f) => stack[top - 1] = stack[top - 1].map(f),
+                Op::Add => binary(&mut stack, &mut top, |a, b| a + b),
+                Op::Sub => binary(&mut stack, &mut top, |a, b| a - b),
+                Op::Mul => binary(&mut stack, &mut top, |a, b| a * b),
+                Op::Div => binary(&mut stack, &mut top, |a, b| a / b),
+                Op::Pow => binary(&mut stack, &mut top, f64::powf),
+                Op::Call2(f) => binary(&mut stack, &mut top, f),
+            }
+        }
+        return stack[0];
+    }
+
+    fn emit(&mut self, expr: &Expr, params: &[String]) -> Result<(), ParseError> {
+        match expr {
+            Expr::Number(v) => self.ops.push(Op::Number(*v)),
+            Expr::Name(name, pos) => {
+                let op = if let Some(i) = COORDINATES.iter().position(|c| c == name) {
+                    Op::Coordinate(i)
+                } else if let Some(i) = params.iter().position(|p| p == name) {
+                    Op::Param(i)
+                } else if let Some((_, v)) = CONSTANTS.iter().find(|c| c.0 == name) {
+                    Op::Number(*v)
+                } else if FUNCTIONS.iter().any(|f| f.0 == name) {
+                    return pos.error(format!("`{name}` is a function, it needs `(...)` after it"));
+                } else {
+                    return pos.error(format!("unknown name `{name}` - give it a value with a `{name} = ...` line"));
+                };
+                self.ops.push(op);
+            }
+            Expr::Neg(inner) => {
+                self.emit(inner, params)?;
+                self.ops.push(Op::Neg);
+            }
+            Expr::Pow(a, b) => {
+                self.emit(a, params)?;
+                self.emit(b, params)?;
+                self.ops.push(Op::Pow);
+            }
+            Expr::Chain(first, rest) => {
+                self.emit(first, params)?;
+                for (op, b) in rest {
+                    self.emit(b, params)?;
+                    self.ops.push(match op {
+                        '+' => Op::Add,
+                        '-' => Op::Sub,
+                        '*' => Op::Mul,
+                        _ => Op::Div,
+                    });
+                    self.fold();
+                }
+            }
+            Expr::Call(name, pos, args) => {
+                let Some((_, function)) = FUNCTIONS.iter().find(|f| f.0 == name) else {
+                    return pos.error(format!("unknown function `{name}`"));
+                };
+                let (arity, op) = match *function {
+                    Function::One(f) => (1, Op::Call1(f)),
+                    Function::Two(f) => (2, Op::Call2(f)),
+                };
+                if args.len() != arity {
+                    let s = if arity == 1 { "" } else { "s" };
+                    return pos.error(format!("`{name}` takes {arity} argument{s}, got {}", args.len()));
+                }
+                for arg in args {
+                    self.emit(arg, params)?;
+                }
+                self.ops.push(op);
+            }
+        }
+        self.fold();
+        return Ok(());
+    }
+
+    //an instruction that only works on numbers gets replaced with its result, so `8/3` is worked out once here
+    fn fold(&mut self) {
+        let n = self.ops.len();
+        let number = |i: usize| match self.ops.get(i) {
+            Some(Op::Number(v)) => Some(*v),
+            _ => None,
+        };
+        let folded = match (self.ops[n - 1], n.checked_sub(2).and_then(number), n.checked_sub(3).and_then(number)) {
+            (Op::Neg, Some(a), _) => Some((1, -a)),
+            (Op::Call1(f), Some(a), _) => Some((1, f(a))),
+            (Op::Add, Some(b), Some(a)) => Some((2, a + b)),
+            (Op::Sub, Some(b), Some(a)) => Some((2, a - b)),
+            (Op::Mul, Some(b), Some(a)) => Some((2, a * b)),
+            (Op::Div, Some(b), Some(a)) => Some((2, a / b)),
+            (Op::Pow, Some(b), Some(a)) => Some((2, a.powf(b))),
+            (Op::Call2(f), Some(b), Some(a)) => Some((2, f(a, b))),
+            _ => None,
+        };
+        if let Some((operands, v)) = folded {
+            self.ops.truncate(n - 1 - operands);
+            self.ops.push(Op::Number(v));
+        }
+    }
+
+    //how many stack slots running it takes
+    fn depth(&self) -> usize {
+        let (mut depth, mut deepest) = (0usize, 0);
+        for op in &self.ops {
+            match op {
+                Op::Number(_) | Op::Coordinate(_) | Op::Param(_) => depth += 1,
+                Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Pow | Op::Call2(_) => depth -= 1,
+                Op::Neg | Op::Call1(_) => {}
+            }
+            deepest = deepest.max(depth);
+        }
+        return deepest;
+    }
+
+    fn compile(expr: &Expr, params: &[String], pos: Pos) -> Result<Self, ParseError> {
+        let mut program = Program { ops: Vec::new() };
+        program.emit(expr, params)?;
+        if program.depth() > STACK_SIZE {
+            return pos.error(format!("too deeply nested, more than {STACK_SIZE} values pending at once"));
+        }
+        return Ok(program);
+    }
+
+    //the value, if it's the same everywhere
+    fn constant(&self) -> Option<f64> {
+        return match self.ops[..] {
+            [Op::Number(v)] => Some(v),
+            _ => None,
+        };
+    }
+}
+
+//a whole system: dx, dy, dz and the parameters they use
+#[derive(Clone, Debug)]
+pub struct Equations {
+    //in the order they were assigned, with their defaults
+    pub params: Vec<(String, f64)>,
+    programs: [Program; 3],
+}
+
+impl Equations {
+    pub fn derivative(&self, at: &Coord, params: &[f64]) -> Coord {
+        let at = [[at.x], [at.y], [at.z]];
+        let [x, y, z] = self.programs.each_ref().map(|p| p.eval(&at, params)[0]);
+        return Coord { x, y, z };
+    }
+
+    pub fn derivative_x4(&self, at: &Coord<F64x4>, params: &[f64]) -> Coord<F64x4> {
+        let at: [[f64; LANES]; 3] = [at.x.to_array(), at.y.to_array(), at.z.to_array()];
+        let [x, y, z] = self.programs.each_ref().map(|p| F64x4::new(p.eval(&at, params)));
+        return Coord { x, y, z };
+    }
+}
+
+pub fn compile(source: &str) -> Result<Equations, ParseError> {
+    let tokens = tokenize(source)?;
+    let end = tokens.last().unwrap().1;
+    let statements = Parser { tokens, next: 0, depth: 0 }.statements()?;
+
+    //every name assigned that isn't an equation is a parameter, in order
+    let mut params: Vec<String> = Vec::new();
+    let mut equations: [Option<(Pos, &Expr)>; 3] = [None; 3];
+    for (name, pos, expr) in &statements {
+        let taken = COORDINATES.contains(&name.as_str()) || CONSTANTS.iter().any(|c| c.0 == name)
+            || FUNCTIONS.iter().any(|f| f.0 == name);
+        if taken {
+            return pos.error(format!("`{name}` can't be assigned to, it's a built in name"));
+        }
+        let seen = params.contains(name) || EQUATIONS.iter().zip(&equations).any(|(e, seen)| e == name && seen.is_some());
+        if seen {
+            return pos.error(format!("`{name}` is assigned twice"));
+        }
+        match EQUATIONS.iter().position(|e| e == name) {
+            Some(i) => equations[i] = Some((*pos, expr)),
+            None => params.push(name.clone()),
+        }
+    }
+
+    let mut defaults = Vec::new();
+    for (name, pos, expr) in statements.iter().filter(|s| !EQUATIONS.contains(&s.0.as_str())) {
+        //anything made of just numbers folds down to one
+        match Program::compile(expr, &params, *pos)?.constant() {
+            Some(v) => defaults.push((name.clone(), v)),
+            None => return pos.error(format!("`{name}` has to be a number, it can't use x, y, z or parameters")),
+        }
+    }
+
+    let mut programs = Vec::new();
+    for (name, equation) in EQUATIONS.iter().zip(equations) {
+        let Some((pos, expr)) = equation else {
+            return end.error(format!("missing the `{name} = ...` line"));
+        };
+        programs.push(Program::compile(expr, &params, pos)?);
+    }
+    let programs: [Program; 3] = programs.try_into().unwrap();
+    return Ok(Equations { params: defaults, programs });
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Registry;
+
+    const LORENZ: &str = "
+        # the one in the registry, parameters in the same order
+        dx = sigma*(y - x)
+        dy = x*(rho - z) - y
+        dz = x*y - beta*z
+        rho = 28; sigma = 10; beta = 8/3
+    ";
+
+    #[test]
+    fn typed_lorenz_matches_builtin() {
+        let equations = compile(LORENZ).unwrap();
+        let names: Vec<&str> = equations.params.iter().map(|p| p.0.as_str()).collect();
+        assert_eq!(names, ["rho", "sigma", "beta"]);
+        assert_eq!(equations.params[2].1, 8.0 / 3.0);
+
+        let registry = Registry::builtin();
+        let lorenz = registry.get(registry.find("lorenz").unwrap());
+        let params = [20.0, 12.0, 2.0];
+        let at = Coord { x: 1.5, y: -2.0, z: 17.0 };
+        let (a, b) = (equations.derivative(&at, &params), lorenz.derivative(&at, &params));
+        assert_eq!((a.x, a.y, a.z), (b.x, b.y, b.z));
+
+        let lanes = Coord { x: F64x4::new([1.5, 0.0, 3.0, -4.0]), y: F64x4::new([-2.0, 1.0, 2.0, 9.0]), z: F64x4::new([17.0, 0.5, 30.0, 1.0]) };
+        let (a, b) = (equations.derivative_x4(&lanes, &params), lorenz.derivative_x4(&lanes, &params));
+        assert_eq!((a.x, a.y, a.z), (b.x, b.y, b.z));
+    }
+
+    #[test]
+    fn precedence_and_functions() {
+        let value = |source: &str| {
+            let equations = compile(&format!("dx = {source}\ndy = 0\ndz = 0")).unwrap();
+            return equations.derivative(&Coord { x: 2.0, y: 3.0, z: 0.0 }, &[]).x;
+        };
+        assert_eq!(value("1 + 2*3^2"), 19.0);
+        assert_eq!(value("-x^2"), -4.0);
+        assert_eq!(value("2^3^2"), 512.0);
+        assert_eq!(value("x - y - 1"), -2.0);
+        assert_eq!(value("max(x, y) / min(x, y)"), 1.5);
+        assert_eq!(value("2e-1 + 1.5E2 + .5"), 150.7);
+        assert_eq!(value("cos(pi)"), -1.0);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let error = |source: &str| compile(source).unwrap_err().to_string();
+        assert_eq!(error("dx = s*(y - x)\ndy = 0\ndz = 0"), "line 1, column 6: unknown name `s` - give it a value with a `s = ...` line");
+        assert_eq!(error("dx = (x + 1\ndy = 0"), "line 1, column 12: expected `)` to close `(`, got the end of the line");
+        assert_eq!(error("dx = x; dy = y"), "line 1, column 15: missing the `dz = ...` line");
+        assert_eq!(error("dx = sin(x, y)"), "line 1, column 6: `sin` takes 1 argument, got 2");
+        assert_eq!(error("dx = x $ 2"), "line 1, column 8: unexpected `$`");
+        assert_eq!(error("a = 1\na = 2"), "line 2, column 1: `a` is assigned twice");
+        assert_eq!(error("a = 2*x"), "line 1, column 1: `a` has to be a number, it can't use x, y, z or parameters");
+        assert_eq!(error("dx = x x"), "line 1, column 8: expected the end of the line, got `x`");
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error_not_a_crash() {
+        let error = |source: String| compile(&source).unwrap_err().to_string();
+        assert!(error(format!("dx = {}x", "-".repeat(10_000))).contains("too deeply nested"));
+        assert!(error(format!("dx = {}x", "(".repeat(10_000))).contains("too deeply nested"));
+        assert!(error(format!("dx = {}x", "x^".repeat(10_000))).contains("too deeply nested"));
+        assert!(error(format!("dx = {}x", "sin(".repeat(10_000))).contains("too deeply nested"));
+        //a long flat chain isn't nested, it only takes two stack slots
+        assert!(compile(&format!("dx = x{}\ndy = 0\ndz = 0", "+x".repeat(10_000))).is_ok());
+    }
+}
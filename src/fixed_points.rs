@@ -0,0 +1,281 @@
+use crate::chaos_equations::Coord;
+use crate::registry::ChaosSystem;
+
+/*
+    Fixed Points
+    where the flow stands still, and what it does right next to them - the eigenvalues of the jacobian there say
+    which directions get pulled in (negative real part), pushed out (positive) and spun around (imaginary part)
+    systems with a closed form list their own (ChaosSystem::equilibria), the rest get found with newton's method
+    from a grid of starting points over the attractor
+*/
+
+//row i is the gradient of the ith component of the derivative
+pub type Matrix = [[f64; 3]; 3];
+
+//(real, imaginary)
+pub type Eigenvalue = (f64, f64);
+
+//newton starts from NEWTON_GRID^3 points spread over the framing cube
+pub const NEWTON_GRID: usize = 5;
+pub const NEWTON_ITERATIONS: usize = 100;
+//real parts closer to 0 than this count as 0
+pub const HYPERBOLIC_TOLERANCE: f64 = 1e-6;
+
+fn to_array(c: &Coord) -> [f64; 3] {
+    return [c.x, c.y, c.z];
+}
+
+fn from_array(a: [f64; 3]) -> Coord {
+    return Coord { x: a[0], y: a[1], z: a[2] };
+}
+
+fn length(c: &Coord) -> f64 {
+    return (c.x * c.x + c.y * c.y + c.z * c.z).sqrt();
+}
+
+//central differences
+pub fn jacobian(system: &ChaosSystem, params: &[f64], at: &Coord) -> Matrix {
+    let mut m = [[0.0; 3]; 3];
+    for j in 0..3 {
+        let mut hi = to_array(at);
+        let mut lo = to_array(at);
+        let h = 1e-6 * (1.0 + hi[j].abs());
+        hi[j] += h;
+        lo[j] -= h;
+        let d = (system.derivative(&from_array(hi), params) - system.derivative(&from_array(lo), params)) * (0.5 / h);
+        for (i, di) in to_array(&d).into_iter().enumerate() {
+            m[i][j] = di;
+        }
+    }
+    return m;
+}
+
+fn det(m: &Matrix) -> f64 {
+    return m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+}
+
+//m x = b by cramer's rule, None if m is singular
+fn solve(m: &Matrix, b: [f64; 3]) -> Option<[f64; 3]> {
+    let d = det(m);
+    if d.abs() < 1e-300 {
+        return None;
+    }
+    let mut x = [0.0; 3];
+    for (k, xk) in x.iter_mut().enumerate() {
+        let mut mk = *m;
+        for i in 0..3 {
+            mk[i][k] = b[i];
+        }
+        *xk = det(&mk) / d;
+    }
+    return Some(x);
+}
+
+//a fixed point near `guess`, if newton gets to one
+pub fn newton(system: &ChaosSystem, params: &[f64], guess: Coord) -> Option<Coord> {
+    let mut at = guess;
+    for _ in 0..NEWTON_ITERATIONS {
+        let f = system.derivative(&at, params);
+        if length(&f) < 1e-10 * (1.0 + length(&at)) {
+            return Some(at);
+        }
+        let step = solve(&jacobian(system, params, &at), to_array(&f))?;
+        at = at - from_array(step);
+        if !length(&at).is_finite() {
+            return None;
+        }
+    }
+    return None;
+}
+
+//the roots of x^3 + a x^2 + b x + c, the real one(s) polished with newton
+fn cubic_roots(a: f64, b: f64, c: f64) -> [Eigenvalue; 3] {
+    //x = t - a/3 gives t^3 + pt + q
+    let shift = a / 3.0;
+    let p = b - a * a / 3.0;
+    let q = 2.0 * a * a * a / 27.0 - a * b / 3.0 + c;
+    let polish = |mut x: f64| {
+        for _ in 0..3 {
+            let f = ((x + a) * x + b) * x + c;
+            let df = (3.0 * x + 2.0 * a) * x + b;
+            if df == 0.0 {
+                break;
+            }
+            x -= f / df;
+        }
+        return x;
+    };
+    let discriminant = (q / 2.0) * (q / 2.0) + (p / 3.0) * (p / 3.0) * (p / 3.0);
+    if discriminant > 0.0 {
+        //one real root, then the other two from what's left once it's divided out
+        let root = discriminant.sqrt();
+        let r = polish((-q / 2.0 + root).cbrt() + (-q / 2.0 - root).cbrt() - shift);
+        let (qb, qc) = (a + r, b + r * (a + r));
+        let d = qb * qb / 4.0 - qc;
+        if d >= 0.0 {
+            return [(r, 0.0), (-qb / 2.0 + d.sqrt(), 0.0), (-qb / 2.0 - d.sqrt(), 0.0)];
+        }
+        return [(r, 0.0), (-qb / 2.0, (-d).sqrt()), (-qb / 2.0, -(-d).sqrt())];
+    }
+    //three real roots, trigonometric form
+    if p == 0.0 {
+        return [(-shift, 0.0); 3];
+    }
+    let m = 2.0 * (-p / 3.0).sqrt();
+    let theta = (3.0 * q / (p * m)).clamp(-1.0, 1.0).acos() / 3.0;
+    let roots = [0.0, 1.0, 2.0].map(|k| polish(m * (theta - k * std::f64::consts::TAU / 3.0).cos() - shift));
+    return roots.map(|r| (r, 0.0));
+}
+
+//largest real part first
+pub fn eigenvalues(m: &Matrix) -> [Eigenvalue; 3] {
+    //the characteristic polynomial is x^3 - tr x^2 + (sum of the principal 2x2 minors) x - det
+    let trace = m[0][0] + m[1][1] + m[2][2];
+    let minors = m[0][0] * m[1][1] - m[0][1] * m[1][0]
+        + m[0][0] * m[2][2] - m[0][2] * m[2][0]
+        + m[1][1] * m[2][2] - m[1][2] * m[2][1];
+    let mut roots = cubic_roots(-trace, minors, -det(m));
+    roots.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.total_cmp(&a.1)));
+    return roots;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stability {
+    //everything nearby falls in
+    Sink,
+    //falls in while spiralling
+    SpiralSink,
+    //everything nearby gets pushed out
+    Source,
+    SpiralSource,
+    //some directions in, `unstable` of them out
+    Saddle { unstable: usize },
+    //same, with a spiral in one of the two
+    SaddleFocus { unstable: usize },
+    //a real part is 0, the linearization can't tell
+    NonHyperbolic,
+}
+
+impl Stability {
+    pub fn classify(eigenvalues: &[Eigenvalue; 3]) -> Self {
+        if eigenvalues.iter().any(|e| e.0.abs() < HYPERBOLIC_TOLERANCE) {
+            return Stability::NonHyperbolic;
+        }
+        let unstable = eigenvalues.iter().filter(|e| e.0 > 0.0).count();
+        let spiral = eigenvalues.iter().any(|e| e.1 != 0.0);
+        return match (unstable, spiral) {
+            (0, false) => Stability::Sink,
+            (0, true) => Stability::SpiralSink,
+            (3, false) => Stability::Source,
+            (3, true) => Stability::SpiralSource,
+            (_, false) => Stability::Saddle { unstable },
+            (_, true) => Stability::SaddleFocus { unstable },
+        };
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Stability::Sink => "sink",
+            Stability::SpiralSink => "spiral sink",
+            Stability::Source => "source",
+            Stability::SpiralSource => "spiral source",
+            Stability::Saddle { .. } => "saddle",
+            Stability::SaddleFocus { .. } => "saddle-focus",
+            Stability::NonHyperbolic => "non-hyperbolic",
+        };
+    }
+}
+
+pub struct FixedPoint {
+    pub at: Coord,
+    pub eigenvalues: [Eigenvalue; 3],
+    pub stability: Stability,
+}
+
+impl FixedPoint {
+    pub fn at(system: &ChaosSystem, params: &[f64], at: Coord) -> Self {
+        let eigenvalues = eigenvalues(&jacobian(system, params, &at));
+        return FixedPoint { at, eigenvalues, stability: Stability::classify(&eigenvalues) };
+    }
+}
+
+//every fixed point of the system, from its closed form if it has one, sorted by x, then y, then z
+pub fn find(system: &ChaosSystem, params: &[f64]) -> Vec<FixedPoint> {
+    let mut found = match system.equilibria {
+        Some(equilibria) => equilibria(params),
+        None => search(system, params),
+    };
+    found.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)).then(a.z.total_cmp(&b.z)));
+    return found.into_iter().map(|at| FixedPoint::at(system, params, at)).collect();
+}
+
+//newton from every point of the grid, without repeats
+fn search(system: &ChaosSystem, params: &[f64]) -> Vec<Coord> {
+    let framing = &system.framing;
+    let spread = |i: usize| (i as f64 / (NEWTON_GRID - 1) as f64 * 2.0 - 1.0) * framing.radius;
+    let mut found: Vec<Coord> = Vec::new();
+    for i in 0..NEWTON_GRID.pow(3) {
+        let offset = Coord { x: spread(i % NEWTON_GRID), y: spread(i / NEWTON_GRID % NEWTON_GRID), z: spread(i / NEWTON_GRID / NEWTON_GRID) };
+        let Some(at) = newton(system, params, framing.center + offset) else {
+            continue;
+        };
+        //far outside the view it's more likely newton wandering off than anything worth marking
+        let near = length(&(at - framing.center)) < framing.radius * 10.0;
+        let new = found.iter().all(|f| length(&(*f - at)) > 1e-6 * (1.0 + length(&at)));
+        if near && new {
+            found.push(at);
+        }
+    }
+    return found;
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Registry;
+
+    #[test]
+    fn lorenz_by_newton_matches_closed_form() {
+        let registry = Registry::builtin();
+        let lorenz = registry.get(registry.find("lorenz").unwrap());
+        let params = lorenz.default_params();
+        let exact = find(lorenz, &params);
+        let numeric = find(&ChaosSystem { equilibria: None, ..lorenz.clone() }, &params);
+        assert_eq!(exact.len(), 3);
+        assert_eq!(numeric.len(), 3);
+        for (e, n) in exact.iter().zip(&numeric) {
+            //both come sorted by x: -s, 0, s
+            assert!(length(&(e.at - n.at)) < 1e-8, "{:?} {:?}", to_array(&e.at), to_array(&n.at));
+            assert_eq!(e.stability, n.stability);
+        }
+
+        //the origin's eigenvalues are known exactly: -beta and (-(sigma + 1) ± sqrt((sigma + 1)^2 + 4 sigma (rho - 1))) / 2
+        let origin = &exact[1];
+        let (rho, sigma, beta): (f64, f64, f64) = (28.0, 10.0, 8.0 / 3.0);
+        let root = ((sigma + 1.0).powi(2) + 4.0 * sigma * (rho - 1.0)).sqrt();
+        let expected = [(-(sigma + 1.0) + root) / 2.0, -beta, (-(sigma + 1.0) - root) / 2.0];
+        for (got, want) in origin.eigenvalues.iter().zip(expected) {
+            assert!((got.0 - want).abs() < 1e-6 && got.1 == 0.0, "{got:?} {want}");
+        }
+        assert_eq!(origin.stability, Stability::Saddle { unstable: 1 });
+        //the wings' centers spiral outwards at rho = 28, past the hopf bifurcation at 24.74
+        assert_eq!(exact[0].stability, Stability::SaddleFocus { unstable: 2 });
+        assert_eq!(exact[2].stability, Stability::SaddleFocus { unstable: 2 });
+    }
+
+    #[test]
+    fn cubic_with_complex_pair() {
+        //(x - 2)(x^2 + 2x + 5): 2 and -1 ± 2i
+        let roots = eigenvalues(&[[2.0, 0.0, 0.0], [0.0, -1.0, 2.0], [0.0, -2.0, -1.0]]);
+        let expected = [(2.0, 0.0), (-1.0, 2.0), (-1.0, -2.0)];
+        for (got, want) in roots.iter().zip(expected) {
+            assert!((got.0 - want.0).abs() < 1e-12 && (got.1 - want.1).abs() < 1e-12, "{roots:?}");
+        }
+    }
+}
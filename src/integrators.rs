@@ -0,0 +1,352 @@
+use crate::chaos_equations::Coord;
+use crate::lanes::{Real, F64x4, LANES};
+
+//anything that can move a point along the flow of an equation
+//`f` returns the derivative at a point, `dt` is how far (in time) to move
+pub trait Integrator: Sync {
+    fn name(&self) -> &'static str;
+    //the error over a fixed stretch of time shrinks like dt^order
+    fn order(&self) -> i32;
+    fn step(&self, f: &dyn Fn(&Coord) -> Coord, at: &Coord, dt: f64) -> Coord;
+
+    //four points at once, see ensemble.rs
+    //the default takes them apart and steps them one by one - fine for anything whose control flow depends on the point
+    fn step_x4(
+        &self,
+        f: &dyn Fn(&Coord) -> Coord,
+        _f_x4: &dyn Fn(&Coord<F64x4>) -> Coord<F64x4>,
+        at: &Coord<F64x4>,
+        dt: f64,
+    ) -> Coord<F64x4> {
+        let (x, y, z) = (at.x.to_array(), at.y.to_array(), at.z.to_array());
+        let mut out = [[0.0; LANES]; 3];
+        for i in 0..LANES {
+            let c = self.step(f, &Coord { x: x[i], y: y[i], z: z[i] }, dt);
+            (out[0][i], out[1][i], out[2][i]) = (c.x, c.y, c.z);
+        }
+        return Coord { x: F64x4::new(out[0]), y: F64x4::new(out[1]), z: F64x4::new(out[2]) };
+    }
+}
+
+//every integrator selectable at runtime, in the order they're cycled through
+pub static INTEGRATORS: &[&dyn Integrator] = &[
+    &Euler,
+    &Midpoint,
+    &Rk4,
+    &RKF45,
+    &DORMAND_PRINCE,
+];
+
+//first integrator whose name contains `name`, ignoring case - "rk4", "euler", "dormand" all work
+pub fn find(name: &str) -> Option<&'static dyn Integrator> {
+    let name = name.to_lowercase();
+    return INTEGRATORS.iter()
+        .find(|i| i.name().to_lowercase().contains(&name))
+        .copied();
+}
+
+/*
+    Fixed Step
+*/
+
+//forward euler - first order, what every equation used to do on its own
+pub struct Euler;
+
+impl Integrator for Euler {
+    fn name(&self) -> &'static str {
+        return "Euler";
+    }
+
+    fn order(&self) -> i32 {
+        return 1;
+    }
+
+    fn step(&self, f: &dyn Fn(&Coord) -> Coord, at: &Coord, dt: f64) -> Coord {
+        return euler(f, at, dt);
+    }
+
+    fn step_x4(&self, _: &dyn Fn(&Coord) -> Coord, f: &dyn Fn(&Coord<F64x4>) -> Coord<F64x4>, at: &Coord<F64x4>, dt: f64) -> Coord<F64x4> {
+        return euler(f, at, dt);
+    }
+}
+
+fn euler<T: Real>(f: &dyn Fn(&Coord<T>) -> Coord<T>, at: &Coord<T>, dt: f64) -> Coord<T> {
+    return *at + f(at) * dt;
+}
+
+//explicit midpoint - second order
+pub struct Midpoint;
+
+impl Integrator for Midpoint {
+    fn name(&self) -> &'static str {
+        return "Midpoint";
+    }
+
+    fn order(&self) -> i32 {
+        return 2;
+    }
+
+    fn step(&self, f: &dyn Fn(&Coord) -> Coord, at: &Coord, dt: f64) -> Coord {
+        return midpoint(f, at, dt);
+    }
+
+    fn step_x4(&self, _: &dyn Fn(&Coord) -> Coord, f: &dyn Fn(&Coord<F64x4>) -> Coord<F64x4>, at: &Coord<F64x4>, dt: f64) -> Coord<F64x4> {
+        return midpoint(f, at, dt);
+    }
+}
+
+fn midpoint<T: Real>(f: &dyn Fn(&Coord<T>) -> Coord<T>, at: &Coord<T>, dt: f64) -> Coord<T> {
+    let k1 = f(at);
+    let k2 = f(&(*at + k1 * (dt / 2.0)));
+    return *at + k2 * dt;
+}
+
+//classic runge-kutta - fourth order
+pub struct Rk4;
+
+impl Integrator for Rk4 {
+    fn name(&self) -> &'static str {
+        return "RK4";
+    }
+
+    fn order(&self) -> i32 {
+        return 4;
+    }
+
+    fn step(&self, f: &dyn Fn(&Coord) -> Coord, at: &Coord, dt: f64) -> Coord {
+        return rk4(f, at, dt);
+    }
+
+    fn step_x4(&self, _: &dyn Fn(&Coord) -> Coord, f: &dyn Fn(&Coord<F64x4>) -> Coord<F64x4>, at: &Coord<F64x4>, dt: f64) -> Coord<F64x4> {
+        return rk4(f, at, dt);
+    }
+}
+
+fn rk4<T: Real>(f: &dyn Fn(&Coord<T>) -> Coord<T>, at: &Coord<T>, dt: f64) -> Coord<T> {
+    let k1 = f(at);
+    let k2 = f(&(*at + k1 * (dt / 2.0)));
+    let k3 = f(&(*at + k2 * (dt / 2.0)));
+    let k4 = f(&(*at + k3 * dt));
+    return *at + (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (dt / 6.0);
+}
+
+/*
+    Adaptive Step
+*/
+
+//butcher tableau of an embedded runge-kutta pair
+//`b` is the higher order solution (the one we keep), `b_low` the lower order one used for the error estimate
+//no `c` nodes - every equation here is autonomous, so they'd never be read
+pub struct EmbeddedTableau {
+    pub a: &'static [&'static [f64]],
+    pub b: &'static [f64],
+    pub b_low: &'static [f64],
+    //order of the lower solution, used to scale the step size
+    pub order: i32,
+}
+
+//integrates over `dt` with as many sub-steps as it takes to keep the local error under the tolerance
+pub struct Adaptive {
+    pub name: &'static str,
+    pub tableau: &'static EmbeddedTableau,
+    pub abs_tol: f64,
+    pub rel_tol: f64,
+}
+
+//the most stages any tableau above has
+const MAX_STAGES: usize = 7;
+//give up on refining after this many sub-steps and take whatever we have
+const MAX_SUBSTEPS: usize = 1000;
+
+impl Adaptive {
+    //one embedded step of size h - returns (higher order result, error estimate)
+    fn try_step(&self, f: &dyn Fn(&Coord) -> Coord, at: &Coord, h: f64) -> (Coord, Coord) {
+        let t = self.tableau;
+        let mut k = [Coord::ZERO; MAX_STAGES];
+        for (i, row) in t.a.iter().enumerate() {
+            let mut y = *at;
+            for (kj, a) in k.iter().zip(row.iter()) {
+                y = y + *kj * (a * h);
+            }
+            k[i] = f(&y);
+        }
+        let mut high = *at;
+        let mut err = Coord::ZERO;
+        for (ki, (b, b_low)) in k.iter().zip(t.b.iter().zip(t.b_low.iter())) {
+            high = high + *ki * (b * h);
+            err = err + *ki * ((b - b_low) * h);
+        }
+        return (high, err);
+    }
+
+    //scaled max-norm of the error; <= 1.0 means the step is accepted
+    fn error_norm(&self, from: &Coord, to: &Coord, err: &Coord) -> f64 {
+        let scale = |a: f64, b: f64| self.abs_tol + self.rel_tol * a.abs().max(b.abs());
+        return (err.x.abs() / scale(from.x, to.x))
+            .max(err.y.abs() / scale(from.y, to.y))
+            .max(err.z.abs() / scale(from.z, to.z));
+    }
+}
+
+impl Integrator for Adaptive {
+    fn name(&self) -> &'static str {
+        return self.name;
+    }
+
+    //of the solution it keeps
+    fn order(&self) -> i32 {
+        return self.tableau.order + 1;
+    }
+
+    fn step(&self, f: &dyn Fn(&Coord) -> Coord, at: &Coord, dt: f64) -> Coord {
+        let mut y = *at;
+        let mut remaining = dt;
+        let mut h = dt;
+        for _ in 0..MAX_SUBSTEPS {
+            if remaining.abs() <= dt.abs() * 1e-12 {
+                return y;
+            }
+            if h.abs() > remaining.abs() {
+                h = remaining;
+            }
+            let (next, err) = self.try_step(f, &y, h);
+            let norm = self.error_norm(&y, &next, &err);
+            //a NaN norm means the particle already blew up, no step size will save it
+            if norm <= 1.0 || norm.is_nan() {
+                y = next;
+                remaining -= h;
+            }
+            //standard step size controller, with a safety factor and limits on how fast h can change
+            let factor = if norm == 0.0 {
+                5.0
+            } else {
+                (0.9 * norm.powf(-1.0 / (self.tableau.order + 1) as f64)).clamp(0.2, 5.0)
+            };
+            h *= if factor.is_finite() { factor } else { 0.2 };
+        }
+        //ran out of sub-steps, finish the interval in one go rather than stalling the frame
+        return self.try_step(f, &y, remaining).0;
+    }
+}
+
+//runge-kutta-fehlberg 4(5)
+pub static RKF45_TABLEAU: EmbeddedTableau = EmbeddedTableau {
+    a: &[
+        &[],
+        &[1.0/4.0],
+        &[3.0/32.0, 9.0/32.0],
+        &[1932.0/2197.0, -7200.0/2197.0, 7296.0/2197.0],
+        &[439.0/216.0, -8.0, 3680.0/513.0, -845.0/4104.0],
+        &[-8.0/27.0, 2.0, -3544.0/2565.0, 1859.0/4104.0, -11.0/40.0],
+    ],
+    b: &[16.0/135.0, 0.0, 6656.0/12825.0, 28561.0/56430.0, -9.0/50.0, 2.0/55.0],
+    b_low: &[25.0/216.0, 0.0, 1408.0/2565.0, 2197.0/4104.0, -1.0/5.0, 0.0],
+    order: 4,
+};
+
+//dormand-prince 5(4), the pair behind most "ode45"s
+pub static DORMAND_PRINCE_TABLEAU: EmbeddedTableau = EmbeddedTableau {
+    a: &[
+        &[],
+        &[1.0/5.0],
+        &[3.0/40.0, 9.0/40.0],
+        &[44.0/45.0, -56.0/15.0, 32.0/9.0],
+        &[19372.0/6561.0, -25360.0/2187.0, 64448.0/6561.0, -212.0/729.0],
+        &[9017.0/3168.0, -355.0/33.0, 46732.0/5247.0, 49.0/176.0, -5103.0/18656.0],
+        &[35.0/384.0, 0.0, 500.0/1113.0, 125.0/192.0, -2187.0/6784.0, 11.0/84.0],
+    ],
+    b: &[35.0/384.0, 0.0, 500.0/1113.0, 125.0/192.0, -2187.0/6784.0, 11.0/84.0, 0.0],
+    b_low: &[5179.0/57600.0, 0.0, 7571.0/16695.0, 393.0/640.0, -92097.0/339200.0, 187.0/2100.0, 1.0/40.0],
+    order: 4,
+};
+
+pub static RKF45: Adaptive = Adaptive {
+    name: "RK45 (Fehlberg)",
+    tableau: &RKF45_TABLEAU,
+    abs_tol: 1e-8,
+    rel_tol: 1e-8,
+};
+
+pub static DORMAND_PRINCE: Adaptive = Adaptive {
+    name: "Dormand-Prince",
+    tableau: &DORMAND_PRINCE_TABLEAU,
+    abs_tol: 1e-8,
+    rel_tol: 1e-8,
+};
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Registry;
+
+    //dx = x, dy = -y: the exact answer after t is (e^t, e^-t, 0)
+    fn exponential(c: &Coord) -> Coord {
+        return Coord { x: c.x, y: -c.y, z: 0.0 };
+    }
+
+    //distance from the exact answer after integrating to t = 1 in steps of dt
+    fn error_at_one(integrator: &dyn Integrator, dt: f64) -> f64 {
+        let mut at = Coord { x: 1.0, y: 1.0, z: 0.0 };
+        let steps = (1.0 / dt).round() as usize;
+        for _ in 0..steps {
+            at = integrator.step(&exponential, &at, dt);
+        }
+        let (dx, dy) = (at.x - 1f64.exp(), at.y - (-1f64).exp());
+        return (dx * dx + dy * dy).sqrt();
+    }
+
+    //halving dt should divide the error by about 2^order
+    #[test]
+    fn fixed_step_order() {
+        for integrator in [&Euler as &dyn Integrator, &Midpoint, &Rk4] {
+            let observed = (error_at_one(integrator, 0.02) / error_at_one(integrator, 0.01)).log2();
+            let expected = integrator.order() as f64;
+            assert!((observed - expected).abs() < 0.1, "{}: order {observed}, expected {expected}", integrator.name());
+        }
+    }
+
+    //a single big step gets split up until the error is down at the tolerance, whatever dt was asked for
+    #[test]
+    fn adaptive_meets_tolerance() {
+        for integrator in [&RKF45, &DORMAND_PRINCE] {
+            for dt in [1.0, 0.1] {
+                let error = error_at_one(integrator, dt);
+                assert!(error < 1e-7, "{} at dt {dt}: off by {error}", integrator.name);
+            }
+        }
+    }
+
+    //the fixed step ones are only ever as good as dt lets them be
+    #[test]
+    fn adaptive_beats_fixed_step() {
+        assert!(error_at_one(&DORMAND_PRINCE, 0.1) < error_at_one(&Rk4, 0.1) / 100.0);
+    }
+
+    //stepping forward and then back by the same negative dt should land where it started - the higher the order, the
+    //closer it gets: euler's error gets blown up on the way back, since running lorenz backwards pulls everything apart
+    #[test]
+    fn negative_dt_retraces() {
+        let registry = Registry::builtin();
+        let lorenz = registry.get(registry.find("lorenz").unwrap());
+        let params = lorenz.default_params();
+        let f = |c: &Coord| lorenz.derivative(c, &params);
+        let start = Coord { x: 1.0, y: 1.0, z: 20.0 };
+        let off: Vec<f64> = INTEGRATORS.iter().map(|integrator| {
+            let mut at = start;
+            for dt in [0.001, -0.001] {
+                for _ in 0..500 {
+                    at = integrator.step(&f, &at, dt);
+                }
+            }
+            let d = at - start;
+            return (d.x * d.x + d.y * d.y + d.z * d.z).sqrt();
+        }).collect();
+        assert!(off.windows(2).all(|pair| pair[1] < pair[0]), "{off:?}");
+        //rk4
+        assert!(off[2] < 1e-6, "{off:?}");
+    }
+}
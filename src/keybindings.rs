@@ -0,0 +1,870 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+use bevy::color::palettes::css::{RED, YELLOW};
+use serde::{Deserialize, Serialize};
+
+/*
+    Keybindings
+    every control is an action, and which keys trigger them comes from keybindings.ron next to
+    the app - written back whenever something gets rebound, and read on startup:
+
+        {
+            StepsUp: ["NumpadAdd", "Period"],
+            PreviousEquation: ["Shift+Tab", "Pad:LeftTrigger"],
+        }
+
+    a key is bevy's KeyCode name, with Ctrl+, Alt+ and/or Shift+ in front for a chord, and Pad: in front of a
+    GamepadButtonType name is that button on any gamepad; actions the file leaves out keep their defaults
+    f1 opens a panel listing every action: up/down to pick one, enter and then a key or button to rebind it (shift+enter to
+    add one instead), delete to unbind it, home to go back to its defaults
+    the sticks and triggers aren't actions, see pan_orbit_camera
+    the zooming triggers aren't in the map, but binding an action to one counts as a conflict, same as two actions on one
+    key
+*/
+
+pub const KEYBINDINGS_FILE: &str = "keybindings.ron";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Action {
+    ClearParticles,
+    NextEquation,
+    PreviousEquation,
+    Equation1,
+    Equation2,
+    Equation3,
+    Equation4,
+    Equation5,
+    Equation6,
+    Equation7,
+    Equation8,
+    Equation9,
+    NextIntegrator,
+    StepsUp,
+    StepsDown,
+    DtUp,
+    DtDown,
+    TogglePause,
+    StepFrame,
+    Reverse,
+    NextParam,
+    PreviousParam,
+    ParamUp,
+    ParamDown,
+    SlowParam,
+    ResetParams,
+    SpawnParticle,
+    SpawnBunch,
+    NewSeed,
+    RestartSeed,
+    SpawnPattern,
+    MoreInBunch,
+    FewerInBunch,
+    BiggerBunch,
+    SmallerBunch,
+    ToggleTrails,
+    LongerTrails,
+    ShorterTrails,
+    MoreFade,
+    LessFade,
+    ColorMode,
+    Colormap,
+    RenderMode,
+    ToggleRecording,
+    RecordingFormat,
+    SaveSnapshot,
+    LoadSnapshot,
+    TwinParticles,
+    ToggleSection,
+    SectionAxis,
+    SectionDirection,
+    SectionUp,
+    SectionDown,
+    ExportSection,
+    Bifurcation,
+    BifurcationAxis,
+    FixedPoints,
+    TypeEquation,
+    Orbit,
+    Pan,
+    Zoom,
+    Keybindings,
+}
+
+//in the order the panel lists them
+pub const ACTIONS: [Action; 62] = [
+    Action::ClearParticles,
+    Action::NextEquation,
+    Action::PreviousEquation,
+    Action::Equation1,
+    Action::Equation2,
+    Action::Equation3,
+    Action::Equation4,
+    Action::Equation5,
+    Action::Equation6,
+    Action::Equation7,
+    Action::Equation8,
+    Action::Equation9,
+    Action::NextIntegrator,
+    Action::StepsUp,
+    Action::StepsDown,
+    Action::DtUp,
+    Action::DtDown,
+    Action::TogglePause,
+    Action::StepFrame,
+    Action::Reverse,
+    Action::NextParam,
+    Action::PreviousParam,
+    Action::ParamUp,
+    Action::ParamDown,
+    Action::SlowParam,
+    Action::ResetParams,
+    Action::SpawnParticle,
+    Action::SpawnBunch,
+    Action::NewSeed,
+    Action::RestartSeed,
+    Action::SpawnPattern,
+    Action::MoreInBunch,
+    Action::FewerInBunch,
+    Action::BiggerBunch,
+    Action::SmallerBunch,
+    Action::ToggleTrails,
+    Action::LongerTrails,
+    Action::ShorterTrails,
+    Action::MoreFade,
+    Action::LessFade,
+    Action::ColorMode,
+    Action::Colormap,
+    Action::RenderMode,
+    Action::ToggleRecording,
+    Action::RecordingFormat,
+    Action::SaveSnapshot,
+    Action::LoadSnapshot,
+    Action::TwinParticles,
+    Action::ToggleSection,
+    Action::SectionAxis,
+    Action::SectionDirection,
+    Action::SectionUp,
+    Action::SectionDown,
+    Action::ExportSection,
+    Action::Bifurcation,
+    Action::BifurcationAxis,
+    Action::FixedPoints,
+    Action::TypeEquation,
+    Action::Orbit,
+    Action::Pan,
+    Action::Zoom,
+    Action::Keybindings,
+];
+
+//the first nine systems in the registry
+pub const EQUATION_ACTIONS: [Action; 9] = [
+    Action::Equation1, Action::Equation2, Action::Equation3,
+    Action::Equation4, Action::Equation5, Action::Equation6,
+    Action::Equation7, Action::Equation8, Action::Equation9,
+];
+
+//held while doing something else, in places that never overlap - so they can share a key
+const MODIFIER_ACTIONS: [Action; 2] = [Action::SlowParam, Action::SpawnBunch];
+
+//gamepad buttons the camera reads directly - the analog triggers zoom it
+const FIXED_BUTTONS: [(GamepadButtonType, &str); 2] = [
+    (GamepadButtonType::LeftTrigger2, "zoom"),
+    (GamepadButtonType::RightTrigger2, "zoom"),
+];
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Action::ClearParticles => "clear particles",
+            Action::NextEquation => "next equation",
+            Action::PreviousEquation => "previous equation",
+            Action::Equation1 => "equation 1",
+            Action::Equation2 => "equation 2",
+            Action::Equation3 => "equation 3",
+            Action::Equation4 => "equation 4",
+            Action::Equation5 => "equation 5",
+            Action::Equation6 => "equation 6",
+            Action::Equation7 => "equation 7",
+            Action::Equation8 => "equation 8",
+            Action::Equation9 => "equation 9",
+            Action::NextIntegrator => "next integrator",
+            Action::StepsUp => "more steps per frame",
+            Action::StepsDown => "fewer steps per frame",
+            Action::DtUp => "increase dt",
+            Action::DtDown => "decrease dt",
+            Action::TogglePause => "pause/resume",
+            Action::StepFrame => "step one frame",
+            Action::Reverse => "run time backwards",
+            Action::NextParam => "next parameter",
+            Action::PreviousParam => "previous parameter",
+            Action::ParamUp => "slide parameter up",
+            Action::ParamDown => "slide parameter down",
+            Action::SlowParam => "slide slowly (hold)",
+            Action::ResetParams => "reset parameters",
+            Action::SpawnParticle => "spawn in the middle",
+            Action::SpawnBunch => "spawn a bunch (hold)",
+            Action::NewSeed => "new seed",
+            Action::RestartSeed => "restart seed",
+            Action::SpawnPattern => "cycle bunch shape",
+            Action::MoreInBunch => "more in a bunch",
+            Action::FewerInBunch => "fewer in a bunch",
+            Action::BiggerBunch => "bigger bunch",
+            Action::SmallerBunch => "smaller bunch",
+            Action::ToggleTrails => "trails on/off",
+            Action::LongerTrails => "longer trails",
+            Action::ShorterTrails => "shorter trails",
+            Action::MoreFade => "more trail fade",
+            Action::LessFade => "less trail fade",
+            Action::ColorMode => "next color mode",
+            Action::Colormap => "next colormap",
+            Action::RenderMode => "cubes/point cloud",
+            Action::ToggleRecording => "start/stop recording",
+            Action::RecordingFormat => "next recording format",
+            Action::SaveSnapshot => "save a snapshot",
+            Action::LoadSnapshot => "load the newest snapshot",
+            Action::TwinParticles => "twin particles on/off",
+            Action::ToggleSection => "poincare section on/off",
+            Action::SectionAxis => "next section axis",
+            Action::SectionDirection => "next section direction",
+            Action::SectionUp => "move section up",
+            Action::SectionDown => "move section down",
+            Action::ExportSection => "export section",
+            Action::Bifurcation => "bifurcation diagram",
+            Action::BifurcationAxis => "next bifurcation coordinate",
+            Action::FixedPoints => "show/hide fixed points",
+            Action::TypeEquation => "type an equation",
+            Action::Orbit => "orbit camera (hold)",
+            Action::Pan => "pan camera (hold)",
+            Action::Zoom => "zoom camera (hold)",
+            Action::Keybindings => "keybindings",
+        };
+    }
+
+    fn default_bindings(&self) -> Vec<Binding> {
+        let key = |key| Binding::Key(Chord::key(key));
+        let shift = |key| Binding::Key(Chord::key(key).shift());
+        let pad = Binding::Pad;
+        return match self {
+            Action::ClearParticles => vec![key(KeyCode::KeyC), pad(GamepadButtonType::East)],
+            Action::NextEquation => vec![key(KeyCode::Tab), pad(GamepadButtonType::RightTrigger)],
+            Action::PreviousEquation => vec![shift(KeyCode::Tab), pad(GamepadButtonType::LeftTrigger)],
+            Action::Equation1 => vec![key(KeyCode::Digit1)],
+            Action::Equation2 => vec![key(KeyCode::Digit2)],
+            Action::Equation3 => vec![key(KeyCode::Digit3)],
+            Action::Equation4 => vec![key(KeyCode::Digit4)],
+            Action::Equation5 => vec![key(KeyCode::Digit5)],
+            Action::Equation6 => vec![key(KeyCode::Digit6)],
+            Action::Equation7 => vec![key(KeyCode::Digit7)],
+            Action::Equation8 => vec![key(KeyCode::Digit8)],
+            Action::Equation9 => vec![key(KeyCode::Digit9)],
+            Action::NextIntegrator => vec![key(KeyCode::KeyI), pad(GamepadButtonType::North)],
+            //. and , for keyboards without a numpad
+            Action::StepsUp => vec![key(KeyCode::NumpadAdd), key(KeyCode::Period)],
+            Action::StepsDown => vec![key(KeyCode::NumpadSubtract), key(KeyCode::Comma)],
+            Action::DtUp => vec![key(KeyCode::BracketRight)],
+            Action::DtDown => vec![key(KeyCode::BracketLeft)],
+            Action::TogglePause => vec![key(KeyCode::KeyH), key(KeyCode::Pause), pad(GamepadButtonType::Start)],
+            Action::StepFrame => vec![key(KeyCode::KeyL), pad(GamepadButtonType::RightThumb)],
+            Action::Reverse => vec![key(KeyCode::Backspace), pad(GamepadButtonType::LeftThumb)],
+            Action::NextParam => vec![key(KeyCode::ArrowDown), pad(GamepadButtonType::DPadDown)],
+            Action::PreviousParam => vec![key(KeyCode::ArrowUp), pad(GamepadButtonType::DPadUp)],
+            Action::ParamUp => vec![key(KeyCode::ArrowRight), pad(GamepadButtonType::DPadRight)],
+            Action::ParamDown => vec![key(KeyCode::ArrowLeft), pad(GamepadButtonType::DPadLeft)],
+            Action::SlowParam => vec![key(KeyCode::ShiftLeft)],
+            Action::ResetParams => vec![key(KeyCode::Home), pad(GamepadButtonType::Select)],
+            Action::SpawnParticle => vec![key(KeyCode::Space), pad(GamepadButtonType::South)],
+            Action::SpawnBunch => vec![key(KeyCode::ShiftLeft), pad(GamepadButtonType::West)],
+            Action::NewSeed => vec![key(KeyCode::KeyN)],
+            Action::RestartSeed => vec![shift(KeyCode::KeyN)],
+            Action::SpawnPattern => vec![key(KeyCode::KeyG)],
+            Action::MoreInBunch => vec![key(KeyCode::Equal)],
+            Action::FewerInBunch => vec![key(KeyCode::Minus)],
+            Action::BiggerBunch => vec![shift(KeyCode::Equal)],
+            Action::SmallerBunch => vec![shift(KeyCode::Minus)],
+            Action::ToggleTrails => vec![key(KeyCode::KeyT)],
+            Action::LongerTrails => vec![key(KeyCode::Quote)],
+            Action::ShorterTrails => vec![key(KeyCode::Semicolon)],
+            Action::MoreFade => vec![shift(KeyCode::Quote)],
+            Action::LessFade => vec![shift(KeyCode::Semicolon)],
+            Action::ColorMode => vec![key(KeyCode::KeyK)],
+            Action::Colormap => vec![key(KeyCode::KeyM)],
+            Action::RenderMode => vec![key(KeyCode::KeyF)],
+            Action::ToggleRecording => vec![key(KeyCode::KeyR)],
+            Action::RecordingFormat => vec![shift(KeyCode::KeyR)],
+            Action::SaveSnapshot => vec![key(KeyCode::F5)],
+            Action::LoadSnapshot => vec![key(KeyCode::F9)],
+            Action::TwinParticles => vec![key(KeyCode::KeyV)],
+            Action::ToggleSection => vec![key(KeyCode::KeyO)],
+            Action::SectionAxis => vec![shift(KeyCode::KeyO)],
+            Action::SectionDirection => vec![Binding::Key(Chord::key(KeyCode::KeyO).ctrl())],
+            Action::SectionUp => vec![key(KeyCode::PageUp)],
+            Action::SectionDown => vec![key(KeyCode::PageDown)],
+            Action::ExportSection => vec![key(KeyCode::KeyP)],
+            Action::Bifurcation => vec![key(KeyCode::KeyB)],
+            Action::BifurcationAxis => vec![shift(KeyCode::KeyB)],
+            Action::FixedPoints => vec![key(KeyCode::KeyJ)],
+            Action::TypeEquation => vec![key(KeyCode::KeyE)],
+            Action::Orbit => vec![key(KeyCode::AltLeft)],
+            Action::Pan => vec![key(KeyCode::ControlLeft)],
+            Action::Zoom => vec![key(KeyCode::KeyZ)],
+            Action::Keybindings => vec![key(KeyCode::F1)],
+        };
+    }
+}
+
+/* Chords */
+
+//ctrl, alt, shift - either side counts
+const MODIFIER_KEYS: [[KeyCode; 2]; 3] = [
+    [KeyCode::ControlLeft, KeyCode::ControlRight],
+    [KeyCode::AltLeft, KeyCode::AltRight],
+    [KeyCode::ShiftLeft, KeyCode::ShiftRight],
+];
+const MODIFIER_NAMES: [&str; 3] = ["Ctrl", "Alt", "Shift"];
+
+fn modifier_index(key: KeyCode) -> Option<usize> {
+    return MODIFIER_KEYS.iter().position(|keys| keys.contains(&key));
+}
+
+//a key, and the modifiers that have to be held with it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Chord {
+    pub key: KeyCode,
+    pub modifiers: [bool; 3],
+}
+
+impl Chord {
+    pub fn key(key: KeyCode) -> Self {
+        return Chord { key, modifiers: [false; 3] };
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.modifiers[0] = true;
+        return self;
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.modifiers[2] = true;
+        return self;
+    }
+
+    //its modifiers are all held, not counting its own key (so holding both shifts doesn't make ShiftLeft into Shift+ShiftLeft)
+    fn modifiers_held(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        return (0..3).all(|i| {
+            return !self.modifiers[i] || MODIFIER_KEYS[i].iter().any(|k| *k != self.key && keys.pressed(*k));
+        });
+    }
+
+    //needs every modifier `other` needs, and more
+    fn narrower_than(&self, other: &Chord) -> bool {
+        return self.key == other.key && self.modifiers != other.modifiers
+            && (0..3).all(|i| self.modifiers[i] || !other.modifiers[i]);
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (held, name) in self.modifiers.iter().zip(MODIFIER_NAMES) {
+            if *held {
+                write!(f, "{name}+")?;
+            }
+        }
+        //the debug name is what the file uses, and what ron reads back
+        return write!(f, "{:?}", self.key);
+    }
+}
+
+impl FromStr for Chord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+        let key: KeyCode = ron::from_str(key).map_err(|_| format!("`{key}` in `{s}` isn't a key"))?;
+        let mut chord = Chord::key(key);
+        for part in parts {
+            let Some(i) = MODIFIER_NAMES.iter().position(|name| name.eq_ignore_ascii_case(part)) else {
+                return Err(format!("`{part}` in `{s}` isn't Ctrl, Alt or Shift"));
+            };
+            chord.modifiers[i] = true;
+        }
+        return Ok(chord);
+    }
+}
+
+/* Bindings */
+
+//what the file calls gamepad buttons: "Pad:South", "Pad:DPadUp"...
+const PAD_PREFIX: &str = "Pad:";
+
+//a key chord, or a button on any connected gamepad
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(into = "String", try_from = "String")]
+pub enum Binding {
+    Key(Chord),
+    Pad(GamepadButtonType),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Binding::Key(chord) => write!(f, "{chord}"),
+            Binding::Pad(button) => write!(f, "{PAD_PREFIX}{button:?}"),
+        };
+    }
+}
+
+impl FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let Some(button) = s.strip_prefix(PAD_PREFIX) else {
+            return Ok(Binding::Key(s.parse()?));
+        };
+        return ron::from_str(button.trim()).map(Binding::Pad).map_err(|_| format!("`{button}` isn't a gamepad button"));
+    }
+}
+
+impl From<Binding> for String {
+    fn from(binding: Binding) -> String {
+        return binding.to_string();
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, String> {
+        return s.parse();
+    }
+}
+
+/* Input Map */
+
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct InputMap(pub BTreeMap<Action, Vec<Binding>>);
+
+impl Default for InputMap {
+    fn default() -> Self {
+        return InputMap(ACTIONS.iter().map(|action| (*action, action.default_bindings())).collect());
+    }
+}
+
+impl InputMap {
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        return self.0.get(&action).map_or(&[], |bindings| bindings.as_slice());
+    }
+
+    //for the overlay, like "KeyE" or "F1/Pad:Start"
+    pub fn describe(&self, action: Action) -> String {
+        return self.bindings(action).iter().map(Binding::to_string).collect::<Vec<_>>().join("/");
+    }
+
+    fn chords(&self, action: Action) -> impl Iterator<Item = &Chord> {
+        return self.bindings(action).iter().filter_map(|binding| match binding {
+            Binding::Key(chord) => Some(chord),
+            Binding::Pad(_) => None,
+        });
+    }
+
+    fn buttons(&self, action: Action) -> impl Iterator<Item = GamepadButtonType> + '_ {
+        return self.bindings(action).iter().filter_map(|binding| match binding {
+            Binding::Key(_) => None,
+            Binding::Pad(button) => Some(*button),
+        });
+    }
+
+    //what else `binding` would trigger, if it's bound to `action`
+    pub fn clashes(&self, action: Action, binding: &Binding) -> Vec<&'static str> {
+        let shared = |other: &Action| MODIFIER_ACTIONS.contains(other) && MODIFIER_ACTIONS.contains(&action);
+        let mut clashes: Vec<&str> = self.0.iter()
+            .filter(|(other, bindings)| **other != action && !shared(other) && bindings.contains(binding))
+            .map(|(other, _)| other.name())
+            .collect();
+        if let Binding::Pad(button) = binding {
+            clashes.extend(FIXED_BUTTONS.iter().filter(|(b, _)| b == button).map(|(_, what)| *what));
+        }
+        return clashes;
+    }
+
+    //how many actions have a binding that does something else too
+    pub fn conflicts(&self) -> usize {
+        return self.0.iter()
+            .filter(|(action, bindings)| bindings.iter().any(|binding| !self.clashes(**action, binding).is_empty()))
+            .count();
+    }
+
+    //shift+tab shouldn't also count as tab - when chords share a key, only the one with the most modifiers held goes off
+    fn shadowed(&self, chord: &Chord, keys: &ButtonInput<KeyCode>) -> bool {
+        return ACTIONS.iter().flat_map(|action| self.chords(*action))
+            .any(|other| other.narrower_than(chord) && other.modifiers_held(keys));
+    }
+
+    pub fn just_pressed(&self, action: Action, keys: &ButtonInput<KeyCode>, pads: &ButtonInput<GamepadButton>) -> bool {
+        let key = self.chords(action).any(|chord| {
+            return keys.just_pressed(chord.key) && chord.modifiers_held(keys) && !self.shadowed(chord, keys);
+        });
+        return key || self.buttons(action).any(|button| pads.get_just_pressed().any(|b| b.button_type == button));
+    }
+
+    pub fn pressed(&self, action: Action, keys: &ButtonInput<KeyCode>, pads: &ButtonInput<GamepadButton>) -> bool {
+        let key = self.chords(action).any(|chord| keys.pressed(chord.key) && chord.modifiers_held(keys));
+        return key || self.buttons(action).any(|button| pads.get_pressed().any(|b| b.button_type == button));
+    }
+}
+
+//the map, the keyboard and the gamepads together, for systems that just want to know if an action happened
+#[derive(SystemParam)]
+pub struct Actions<'w> {
+    map: Res<'w, InputMap>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    pads: Res<'w, ButtonInput<GamepadButton>>,
+}
+
+impl Actions<'_> {
+    pub fn just_pressed(&self, action: Action) -> bool {
+        return self.map.just_pressed(action, &self.keys, &self.pads);
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        return self.map.pressed(action, &self.keys, &self.pads);
+    }
+}
+
+/* File */
+
+fn read_file(text: &str) -> Result<InputMap, String> {
+    let from_file: BTreeMap<Action, Vec<Binding>> = ron::from_str(text).map_err(|e| e.to_string())?;
+    let mut map = InputMap::default();
+    map.0.extend(from_file);
+    return Ok(map);
+}
+
+fn load_keybindings(mut map: ResMut<InputMap>, mut panel: ResMut<KeybindingPanel>) {
+    let Ok(text) = std::fs::read_to_string(KEYBINDINGS_FILE) else {
+        return;
+    };
+    match read_file(&text) {
+        Ok(loaded) => {
+            *map = loaded;
+            let conflicts = map.conflicts();
+            if conflicts > 0 {
+                warn!("{KEYBINDINGS_FILE}: {conflicts} actions have keys or buttons that do something else too");
+            }
+        }
+        Err(e) => {
+            error!("{KEYBINDINGS_FILE}: {e}");
+            panel.status = Some(format!("{KEYBINDINGS_FILE}: {e}"));
+        }
+    }
+}
+
+fn save_keybindings(map: &InputMap) -> Result<(), String> {
+    let text = ron::ser::to_string_pretty(&map.0, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+    return std::fs::write(KEYBINDINGS_FILE, text).map_err(|e| format!("{KEYBINDINGS_FILE}: {e}"));
+}
+
+/* Panel */
+
+#[derive(Resource, Default)]
+pub struct KeybindingPanel {
+    pub open: bool,
+    selected: usize,
+    //waiting for a key to bind, and whether it goes next to the old ones
+    capturing: Option<bool>,
+    //a modifier pressed since then, which becomes the binding if it's let go before any other key
+    lone_modifier: Option<KeyCode>,
+    //what the file or the last rebind did
+    pub status: Option<String>,
+}
+
+impl KeybindingPanel {
+    //for the overlay
+    pub fn summary(&self, map: &InputMap) -> String {
+        let open = map.describe(Action::Keybindings);
+        if let Some(status) = &self.status {
+            return format!("keys: {status}");
+        }
+        return match map.conflicts() {
+            0 => format!("keys: {open} to rebind"),
+            n => format!("keys: {n} in conflict, {open} to see them"),
+        };
+    }
+}
+
+#[derive(Component)]
+struct KeybindingText;
+
+//how many actions the panel shows at once, it scrolls to keep the picked one in the middle
+const PANEL_ROWS: usize = 24;
+
+fn open_panel(actions: Actions, mut panel: ResMut<KeybindingPanel>) {
+    if actions.just_pressed(Action::Keybindings) {
+        panel.open = true;
+    }
+}
+
+fn bind(map: &mut InputMap, action: Action, binding: Binding, add: bool) -> Option<String> {
+    let bindings = map.0.entry(action).or_default();
+    if !add {
+        bindings.clear();
+    }
+    if !bindings.contains(&binding) {
+        bindings.push(binding);
+    }
+    return save_keybindings(map).err();
+}
+
+//while the panel is open every key and button goes into it, and the rest of the app sees none of them
+fn panel_input(
+    mut events: EventReader<KeyboardInput>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut pads: ResMut<ButtonInput<GamepadButton>>,
+    mut map: ResMut<InputMap>,
+    mut panel: ResMut<KeybindingPanel>,
+    //kept up to date even while closed, since a modifier can be held down before the panel opens
+    mut held: Local<[bool; 3]>,
+) {
+    let open = panel.open;
+    for event in events.read() {
+        let pressed = event.state == ButtonState::Pressed;
+        let modifier = modifier_index(event.key_code);
+        if let Some(i) = modifier {
+            held[i] = pressed;
+        }
+        if !panel.open {
+            continue;
+        }
+        let action = ACTIONS[panel.selected];
+        if let Some(add) = panel.capturing {
+            //a modifier on its own is bound when it's let go, so it can still start a chord
+            let chord = match (modifier, pressed) {
+                _ if pressed && event.key_code == KeyCode::Escape => {
+                    panel.capturing = None;
+                    continue;
+                }
+                (None, true) => Chord { key: event.key_code, modifiers: *held },
+                (Some(_), true) => {
+                    panel.lone_modifier = Some(event.key_code);
+                    continue;
+                }
+                (Some(_), false) if panel.lone_modifier == Some(event.key_code) => Chord::key(event.key_code),
+                _ => continue,
+            };
+            panel.status = bind(&mut map, action, Binding::Key(chord), add);
+            panel.capturing = None;
+            continue;
+        }
+        if !pressed {
+            continue;
+        }
+        match event.key_code {
+            KeyCode::ArrowDown => panel.selected = (panel.selected + 1) % ACTIONS.len(),
+            KeyCode::ArrowUp => panel.selected = (panel.selected + ACTIONS.len() - 1) % ACTIONS.len(),
+            KeyCode::Enter | KeyCode::NumpadEnter => (panel.capturing, panel.lone_modifier) = (Some(held[2]), None),
+            KeyCode::Delete | KeyCode::Backspace => {
+                map.0.insert(action, Vec::new());
+                panel.status = save_keybindings(&map).err();
+            }
+            KeyCode::Home => {
+                map.0.insert(action, action.default_bindings());
+                panel.status = save_keybindings(&map).err();
+            }
+            KeyCode::Escape => panel.open = false,
+            key if map.chords(Action::Keybindings).any(|chord| chord.key == key) => panel.open = false,
+            _ => {}
+        }
+    }
+    if let (true, Some(add)) = (panel.open, panel.capturing) {
+        if let Some(button) = pads.get_just_pressed().next() {
+            panel.status = bind(&mut map, ACTIONS[panel.selected], Binding::Pad(button.button_type), add);
+            panel.capturing = None;
+        }
+    }
+    if open {
+        keys.reset_all();
+        pads.reset_all();
+    }
+}
+
+fn init_panel_text(mut cmd: Commands) {
+    cmd.spawn((KeybindingText, TextBundle {
+        text: Text::default(),
+        style: Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            left: Val::Percent(40.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+        visibility: Visibility::Hidden,
+        ..default()
+    }));
+}
+
+//one line per action: its keys, and in red whatever else they do
+fn display_panel(
+    map: Res<InputMap>,
+    panel: Res<KeybindingPanel>,
+    mut q_text: Query<(&mut Text, &mut Visibility), With<KeybindingText>>,
+) {
+    if !map.is_changed() && !panel.is_changed() {
+        return;
+    }
+    let Ok((mut text, mut visibility)) = q_text.get_single_mut() else {
+        return;
+    };
+    *visibility = if panel.open { Visibility::Visible } else { Visibility::Hidden };
+    if !panel.open {
+        return;
+    }
+    let plain = TextStyle::default();
+    let mut sections = vec![TextSection::new(
+        "up/down pick, enter then a key rebinds (shift+enter adds), delete unbinds, home resets, esc closes\n\n",
+        plain.clone(),
+    )];
+    let first = panel.selected.saturating_sub(PANEL_ROWS / 2).min(ACTIONS.len() - PANEL_ROWS);
+    if first > 0 {
+        sections.push(TextSection::new(format!("({first} more)\n"), plain.clone()));
+    }
+    for (i, action) in ACTIONS.iter().enumerate().skip(first).take(PANEL_ROWS) {
+        let bindings = map.bindings(*action);
+        let keys = match bindings {
+            _ if i == panel.selected && panel.capturing.is_some() => "press a key or a gamepad button...".to_string(),
+            [] => "-".to_string(),
+            bindings => bindings.iter().map(Binding::to_string).collect::<Vec<_>>().join(", "),
+        };
+        let style = TextStyle {
+            color: if i == panel.selected { Color::Srgba(YELLOW) } else { Color::WHITE },
+            ..default()
+        };
+        sections.push(TextSection::new(format!("{:<24} {keys}", action.name()), style));
+        let mut clashes: Vec<&str> = bindings.iter().flat_map(|binding| map.clashes(*action, binding)).collect();
+        clashes.sort_unstable();
+        clashes.dedup();
+        if !clashes.is_empty() {
+            let style = TextStyle { color: Color::Srgba(RED), ..default() };
+            sections.push(TextSection::new(format!("  also {}", clashes.join(", ")), style));
+        }
+        sections.push(TextSection::new("\n", plain.clone()));
+    }
+    let after = ACTIONS.len() - first - PANEL_ROWS;
+    if after > 0 {
+        sections.push(TextSection::new(format!("({after} more)\n"), plain.clone()));
+    }
+    if let Some(status) = &panel.status {
+        sections.push(TextSection::new(format!("\n{status}"), plain));
+    }
+    text.sections = sections;
+}
+
+pub struct KeybindingsPlugin;
+
+impl Plugin for KeybindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<InputMap>()
+            .init_resource::<KeybindingPanel>()
+            .add_systems(Startup, load_keybindings)
+            .add_systems(Startup, init_panel_text)
+            .add_systems(PreUpdate, panel_input.after(InputSystem))
+            .add_systems(Update, open_panel)
+            .add_systems(Update, display_panel.after(open_panel));
+    }
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_round_trip() {
+        let bindings = [
+            Binding::Key(Chord::key(KeyCode::Tab)),
+            Binding::Key(Chord::key(KeyCode::Tab).shift()),
+            Binding::Key(Chord { key: KeyCode::KeyS, modifiers: [true, true, false] }),
+            Binding::Pad(GamepadButtonType::South),
+            Binding::Pad(GamepadButtonType::RightTrigger2),
+        ];
+        for binding in bindings {
+            let text = ron::to_string(&binding).unwrap();
+            assert_eq!(ron::from_str::<Binding>(&text).unwrap(), binding, "{text}");
+            assert_eq!(binding.to_string().parse::<Binding>().unwrap(), binding);
+        }
+        assert_eq!(Binding::Key(Chord::key(KeyCode::Tab).shift()).to_string(), "Shift+Tab");
+        assert_eq!(Binding::Pad(GamepadButtonType::South).to_string(), "Pad:South");
+        //modifiers are case-insensitive and can come in any order
+        assert_eq!("alt + ctrl + KeyS".parse::<Chord>().unwrap(), Chord { key: KeyCode::KeyS, modifiers: [true, true, false] });
+        assert!("Hyper+KeyS".parse::<Chord>().is_err());
+        assert!("Pad:Nothing".parse::<Binding>().is_err());
+    }
+
+    #[test]
+    fn file_merges_over_defaults() {
+        let map = read_file(r#"{StepsUp: ["Period"], PreviousEquation: ["Shift+Tab", "Pad:Select"], ClearParticles: []}"#).unwrap();
+        assert_eq!(map.bindings(Action::StepsUp), [Binding::Key(Chord::key(KeyCode::Period))]);
+        assert_eq!(map.bindings(Action::PreviousEquation)[1], Binding::Pad(GamepadButtonType::Select));
+        assert!(map.bindings(Action::ClearParticles).is_empty());
+        //everything else is untouched
+        let defaults = InputMap::default();
+        for action in ACTIONS {
+            if ![Action::StepsUp, Action::PreviousEquation, Action::ClearParticles].contains(&action) {
+                assert_eq!(map.bindings(action), defaults.bindings(action), "{action:?}");
+            }
+        }
+        assert!(read_file("{NotAnAction: [\"KeyA\"]}").is_err());
+        assert!(read_file("{StepsUp: [\"Ctrl+NotAKey\"]}").is_err());
+    }
+
+    #[test]
+    fn narrower_chord_shadows() {
+        let map = InputMap::default();
+        let pads = ButtonInput::<GamepadButton>::default();
+        let mut keys = ButtonInput::<KeyCode>::default();
+        keys.press(KeyCode::Tab);
+        assert!(map.just_pressed(Action::NextEquation, &keys, &pads));
+        assert!(!map.just_pressed(Action::PreviousEquation, &keys, &pads));
+
+        let mut keys = ButtonInput::<KeyCode>::default();
+        keys.press(KeyCode::ShiftRight);
+        keys.press(KeyCode::Tab);
+        assert!(map.just_pressed(Action::PreviousEquation, &keys, &pads));
+        assert!(!map.just_pressed(Action::NextEquation, &keys, &pads));
+
+        let mut keys = ButtonInput::<KeyCode>::default();
+        keys.press(KeyCode::ControlLeft);
+        keys.press(KeyCode::KeyO);
+        assert!(map.just_pressed(Action::SectionDirection, &keys, &pads));
+        assert!(!map.just_pressed(Action::ToggleSection, &keys, &pads));
+        assert!(!map.just_pressed(Action::SectionAxis, &keys, &pads));
+    }
+
+    #[test]
+    fn clashes_and_conflicts() {
+        let mut map = InputMap::default();
+        assert_eq!(map.conflicts(), 0);
+        //the modifiers share a key on purpose
+        assert!(map.clashes(Action::SlowParam, &Binding::Key(Chord::key(KeyCode::ShiftLeft))).is_empty());
+
+        let t = Binding::Key(Chord::key(KeyCode::KeyT));
+        assert_eq!(map.clashes(Action::StepsUp, &t), [Action::ToggleTrails.name()]);
+        assert_eq!(map.clashes(Action::StepsUp, &Binding::Pad(GamepadButtonType::RightTrigger2)), ["zoom"]);
+        assert_eq!(map.clashes(Action::StepsUp, &Binding::Key(Chord::key(KeyCode::KeyC))), [Action::ClearParticles.name()]);
+
+        map.0.insert(Action::StepsUp, vec![t]);
+        map.0.insert(Action::StepsDown, vec![Binding::Key(Chord::key(KeyCode::KeyC))]);
+        //two pairs of actions sharing a key
+        assert_eq!(map.conflicts(), 4);
+    }
+}
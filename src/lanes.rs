@@ -0,0 +1,137 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use wide::f64x4;
+
+/*
+    Lanes
+    the equations are written once, generic over `Real`, and get instantiated both for plain f64
+    and for F64x4 - four particles side by side, with every operation done on all four by simd instructions
+*/
+
+//what an equation needs from a number - constants always go on the right (`x * 2.0`, not `2.0 * x`)
+pub trait Real:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + Add<f64, Output = Self>
+    + Sub<f64, Output = Self>
+    + Mul<f64, Output = Self>
+    + Div<f64, Output = Self>
+{
+    fn splat(v: f64) -> Self;
+    fn sin(self) -> Self;
+    fn abs(self) -> Self;
+}
+
+impl Real for f64 {
+    fn splat(v: f64) -> Self {
+        return v;
+    }
+
+    fn sin(self) -> Self {
+        return f64::sin(self);
+    }
+
+    fn abs(self) -> Self {
+        return f64::abs(self);
+    }
+}
+
+pub const LANES: usize = 4;
+
+//four f64s that every operation is applied to at once - wide's f64x4, which is two sse2 instructions per operation
+//(one with avx), whatever the optimizer makes of the code around it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct F64x4(f64x4);
+
+impl F64x4 {
+    pub fn new(lanes: [f64; LANES]) -> Self {
+        return F64x4(f64x4::new(lanes));
+    }
+
+    pub fn to_array(self) -> [f64; LANES] {
+        return self.0.to_array();
+    }
+}
+
+impl Real for F64x4 {
+    fn splat(v: f64) -> Self {
+        return F64x4(f64x4::splat(v));
+    }
+
+    //one lane at a time through the same sin as f64's - wide's own is a different approximation, and the lanes have to
+    //land exactly where stepping one particle at a time would
+    fn sin(self) -> Self {
+        return F64x4::new(self.to_array().map(f64::sin));
+    }
+
+    fn abs(self) -> Self {
+        return F64x4(self.0.abs());
+    }
+}
+
+impl Add for F64x4 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        return F64x4(self.0 + other.0);
+    }
+}
+
+impl Sub for F64x4 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        return F64x4(self.0 - other.0);
+    }
+}
+
+impl Mul for F64x4 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        return F64x4(self.0 * other.0);
+    }
+}
+
+impl Neg for F64x4 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        return F64x4(-self.0);
+    }
+}
+
+impl Add<f64> for F64x4 {
+    type Output = Self;
+
+    fn add(self, k: f64) -> Self {
+        return F64x4(self.0 + f64x4::splat(k));
+    }
+}
+
+impl Sub<f64> for F64x4 {
+    type Output = Self;
+
+    fn sub(self, k: f64) -> Self {
+        return F64x4(self.0 - f64x4::splat(k));
+    }
+}
+
+impl Mul<f64> for F64x4 {
+    type Output = Self;
+
+    fn mul(self, k: f64) -> Self {
+        return F64x4(self.0 * f64x4::splat(k));
+    }
+}
+
+impl Div<f64> for F64x4 {
+    type Output = Self;
+
+    fn div(self, k: f64) -> Self {
+        return F64x4(self.0 / f64x4::splat(k));
+    }
+}
@@ -0,0 +1,21 @@
+//explicit returns everywhere, on purpose
+#![allow(clippy::needless_return)]
+
+//the simulation core - equations, integrators, the system registry, the ensemble stepper and the analysis on top, with no bevy in sight
+//the app in main.rs is built on top of this, and so are the benchmarks
+
+pub mod bifurcation;
+pub mod chaos_equations;
+pub mod colormap;
+pub mod ensemble;
+pub mod export;
+pub mod expression;
+pub mod fixed_points;
+pub mod integrators;
+pub mod lanes;
+pub mod lyapunov;
+pub mod poincare;
+pub mod registry;
+pub mod snapshot;
+pub mod spawn;
+pub mod user_systems;
@@ -0,0 +1,150 @@
+use crate::chaos_equations::Coord;
+use crate::integrators::Integrator;
+use crate::registry::ChaosSystem;
+
+/*
+    Lyapunov Exponents
+    how fast nearby trajectories separate (or close in), one rate per direction, in 1/time units
+    benettin's method: next to a reference point, follow three tiny offsets, and after every step
+    straighten them back out with gram-schmidt - the log of how much each one stretched, averaged over time, is its exponent
+    a positive first exponent is chaos; for a flow one of them is ~0 (along the trajectory), and they add up to the average divergence
+*/
+
+//how far the offset copies start from the reference, small enough to stay linear for one step
+pub const SEPARATION: f64 = 1e-8;
+
+fn dot(a: &Coord, b: &Coord) -> f64 {
+    return a.x * b.x + a.y * b.y + a.z * b.z;
+}
+
+pub struct Lyapunov {
+    //the reference point
+    pub at: Coord,
+    //orthonormal directions of the offsets, most stretched first
+    basis: [Coord; 3],
+    //log growth along each direction so far
+    sums: [f64; 3],
+    //simulated time the sums cover
+    pub time: f64,
+}
+
+impl Lyapunov {
+    pub fn new(at: Coord) -> Self {
+        return Lyapunov {
+            at,
+            basis: [
+                Coord { x: 1.0, y: 0.0, z: 0.0 },
+                Coord { x: 0.0, y: 1.0, z: 0.0 },
+                Coord { x: 0.0, y: 0.0, z: 1.0 },
+            ],
+            sums: [0.0; 3],
+            time: 0.0,
+        };
+    }
+
+    pub fn step(&mut self, system: &ChaosSystem, params: &[f64], integrator: &dyn Integrator, dt: f64) {
+        let f = |c: &Coord| system.derivative(c, params);
+        let next = integrator.step(&f, &self.at, dt);
+        for i in 0..3 {
+            let moved = integrator.step(&f, &(self.at + self.basis[i] * SEPARATION), dt);
+            let mut w = (moved - next) * (1.0 / SEPARATION);
+            for j in 0..i {
+                w = w - self.basis[j] * dot(&w, &self.basis[j]);
+            }
+            let stretch = dot(&w, &w).sqrt();
+            self.sums[i] += stretch.ln();
+            self.basis[i] = w * (1.0 / stretch);
+        }
+        self.at = next;
+        self.time += dt;
+    }
+
+    //forget what was measured so far but keep going from here - for dropping the transient
+    pub fn restart(&mut self) {
+        self.sums = [0.0; 3];
+        self.time = 0.0;
+    }
+
+    //largest first, NaN until a step has been taken
+    pub fn exponents(&self) -> [f64; 3] {
+        return self.sums.map(|s| s / self.time);
+    }
+
+    //false once the reference point or an offset went off to infinity
+    pub fn is_finite(&self) -> bool {
+        return [self.at.x, self.at.y, self.at.z].iter().chain(&self.sums).all(|v| v.is_finite());
+    }
+}
+
+//the whole spectrum, measured over `steps` steps after `transient` steps to settle onto the attractor
+pub fn spectrum(
+    system: &ChaosSystem,
+    params: &[f64],
+    integrator: &dyn Integrator,
+    at: Coord,
+    dt: f64,
+    transient: usize,
+    steps: usize,
+) -> [f64; 3] {
+    let mut lyapunov = Lyapunov::new(at);
+    for _ in 0..transient {
+        lyapunov.step(system, params, integrator, dt);
+    }
+    lyapunov.restart();
+    for _ in 0..steps {
+        lyapunov.step(system, params, integrator, dt);
+    }
+    return lyapunov.exponents();
+}
+
+//least squares line through (x, y) points, as (slope, intercept) - None for fewer than two distinct x
+//fitting ln(distance) against time this way gives the largest exponent from two nearby trajectories
+pub fn fit_line(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.0 - mean_x)).sum();
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    if points.len() < 2 || sxx == 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    return Some((slope, mean_y - slope * mean_x));
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrators;
+    use crate::registry::Registry;
+
+    #[test]
+    fn lorenz_spectrum() {
+        let registry = Registry::builtin();
+        let lorenz = registry.get(registry.find("lorenz").unwrap());
+        let params = lorenz.default_params();
+        let rk4 = integrators::find("rk4").unwrap();
+        let [l1, l2, l3] = spectrum(lorenz, &params, rk4, Coord { x: 1.0, y: 1.0, z: 1.0 }, 0.01, 1000, 50_000);
+        //the textbook values are 0.906, 0, -14.572
+        assert!((l1 - 0.906).abs() < 0.05, "{l1}");
+        assert!(l2.abs() < 0.05, "{l2}");
+        assert!((l3 + 14.572).abs() < 0.05, "{l3}");
+        //the divergence of lorenz is the same everywhere, -(sigma + 1 + beta)
+        let sigma = params[lorenz.param_index("sigma").unwrap()];
+        let beta = params[lorenz.param_index("beta").unwrap()];
+        assert!((l1 + l2 + l3 + sigma + 1.0 + beta).abs() < 1e-3, "{}", l1 + l2 + l3);
+    }
+
+    #[test]
+    fn fits_a_line() {
+        let points: Vec<(f64, f64)> = (0..10).map(|i| (i as f64, 0.5 * i as f64 - 3.0)).collect();
+        let (slope, intercept) = fit_line(&points).unwrap();
+        assert!((slope - 0.5).abs() < 1e-12 && (intercept + 3.0).abs() < 1e-12);
+        assert!(fit_line(&points[..1]).is_none());
+        assert!(fit_line(&[(1.0, 0.0), (1.0, 2.0)]).is_none());
+    }
+}
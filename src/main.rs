@@ -1,13 +1,35 @@
-use bevy::ecs::system::SystemId;
+//explicit returns everywhere, on purpose
+#![allow(clippy::needless_return)]
+
+use bevy::ecs::system::{SystemId, SystemParam};
 use bevy::input::common_conditions::input_pressed;
 use bevy::{prelude::*, window::PrimaryWindow};
 use bevy::color::palettes::css::*;
 use rand::Rng;
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
-
-mod chaos_equations;
+use bevy::tasks::ComputeTaskPool;
+
+mod coloring;
+mod equilibria;
+mod keybindings;
+mod point_cloud;
+mod prompt;
+mod recording;
+mod saving;
+mod section;
+mod sensitivity;
+mod settings;
+mod spawning;
+mod sweeping;
+mod trails;
+
+//the simulation itself lives in the library (src/lib.rs), everything here is the bevy app around it
+use chaos_core::{chaos_equations, colormap, export, integrators, registry, snapshot, spawn};
+use chaos_core::ensemble::Ensemble;
 
 use chaos_equations as chaos;
+use keybindings::{Action, Actions};
+use settings::SimSettings;
 
 /*
     Stolen Camera Code
@@ -40,18 +62,18 @@ pub struct PanOrbitSettings {
     pub orbit_sensitivity: f32,
     /// Exponent per pixel of mouse motion
     pub zoom_sensitivity: f32,
-    /// Key to hold for panning
-    pub pan_key: Option<KeyCode>,
-    /// Key to hold for orbiting
-    pub orbit_key: Option<KeyCode>,
-    /// Key to hold for zooming
-    pub zoom_key: Option<KeyCode>,
     /// What action is bound to the scroll wheel?
     pub scroll_action: Option<PanOrbitAction>,
     /// For devices with a notched scroll wheel, like desktop mice
     pub scroll_line_sensitivity: f32,
     /// For devices with smooth scrolling, like touchpads
     pub scroll_pixel_sensitivity: f32,
+    /// Pixels of mouse motion per second a gamepad stick is worth when pushed all the way
+    /// (the left one orbits, the right one pans)
+    pub stick_sensitivity: f32,
+    /// Pixels of mouse motion per second a gamepad trigger is worth when pulled all the way
+    /// (the right one zooms in, the left one out)
+    pub trigger_sensitivity: f32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -79,12 +101,11 @@ impl Default for PanOrbitSettings {
             pan_sensitivity: 0.001, // 1000 pixels per world unit
             orbit_sensitivity: 0.1f32.to_radians(), // 0.1 degree per pixel
             zoom_sensitivity: 0.01,
-            pan_key: Some(KeyCode::ControlLeft),
-            orbit_key: Some(KeyCode::AltLeft),
-            zoom_key: Some(KeyCode::KeyZ),
             scroll_action: Some(PanOrbitAction::Zoom),
             scroll_line_sensitivity: 16.0, // 1 "line" == 16 "pixels of motion"
             scroll_pixel_sensitivity: 1.0,
+            stick_sensitivity: 1000.0, // about 100 degrees per second of orbit
+            trigger_sensitivity: 100.0, // about e times closer per second
         }
     }
 }
@@ -100,17 +121,51 @@ fn spawn_camera(mut commands: Commands) {
     camera.state.radius = 400.0;
     camera.state.pitch = 0.0; //15.0f32.to_radians();
     camera.state.yaw = 0.0; //30.0f32.to_radians();
-    commands.spawn(camera);
+    //the divergence plot has a camera of its own on top of this one, but the overlay text belongs here
+    commands.spawn((camera, IsDefaultUiCamera));
 }
 
+use bevy::input::gamepad::{GamepadAxis, GamepadAxisType, GamepadButtonType, Gamepads};
 use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
 
 use std::f32::consts::{FRAC_PI_2, PI, TAU};
 
+// The analog side of every connected gamepad
+#[derive(SystemParam)]
+struct GamepadSticks<'w> {
+    gamepads: Res<'w, Gamepads>,
+    sticks: Res<'w, Axis<GamepadAxis>>,
+    triggers: Res<'w, Axis<GamepadButton>>,
+}
+
+impl GamepadSticks<'_> {
+    // The left stick, the right stick, and the left trigger minus the right one,
+    // added up over all gamepads - pushed all the way is 1
+    // (stick Y is up, same as the mouse motion once pan_orbit_camera has flipped it)
+    fn read(&self) -> (Vec2, Vec2, f32) {
+        let mut left_stick = Vec2::ZERO;
+        let mut right_stick = Vec2::ZERO;
+        let mut total_trigger = 0.0;
+        for gamepad in self.gamepads.iter() {
+            let axis = |axis_type| self.sticks.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.0);
+            let trigger = |button_type| self.triggers.get(GamepadButton::new(gamepad, button_type)).unwrap_or(0.0);
+            left_stick += Vec2::new(axis(GamepadAxisType::LeftStickX), axis(GamepadAxisType::LeftStickY));
+            right_stick += Vec2::new(axis(GamepadAxisType::RightStickX), axis(GamepadAxisType::RightStickY));
+            total_trigger += trigger(GamepadButtonType::LeftTrigger2) - trigger(GamepadButtonType::RightTrigger2);
+        }
+        return (left_stick, right_stick, total_trigger);
+    }
+}
+
 fn pan_orbit_camera(
-    kbd: Res<ButtonInput<KeyCode>>,
+    // the keys to hold for panning, orbiting and zooming are in the input map
+    actions: Actions,
     mut evr_motion: EventReader<MouseMotion>,
     mut evr_scroll: EventReader<MouseWheel>,
+    pads: GamepadSticks,
+    time: Res<Time>,
+    // whether the left stick was orbiting last frame
+    mut stick_orbiting: Local<bool>,
     mut q_camera: Query<(
         &PanOrbitSettings,
         &mut PanOrbitState,
@@ -143,13 +198,22 @@ fn pan_orbit_camera(
         }
     }
 
+    let (left_stick, right_stick, total_trigger) = pads.read();
+    let stick_orbit_started = left_stick != Vec2::ZERO && !*stick_orbiting;
+    *stick_orbiting = left_stick != Vec2::ZERO;
+
     for (settings, mut state, mut transform) in &mut q_camera {
+        // The sticks and triggers work like dragging the mouse,
+        // at a speed rather than by a distance
+        let stick_motion = settings.stick_sensitivity * time.delta_seconds();
+        let trigger_motion = settings.trigger_sensitivity * time.delta_seconds();
+
         // Check how much of each thing we need to apply.
         // Accumulate values from motion and scroll,
         // based on our configuration settings.
 
         let mut total_pan = Vec2::ZERO;
-        if settings.pan_key.map(|key| kbd.pressed(key)).unwrap_or(false) {
+        if actions.pressed(Action::Pan) {
             total_pan -= total_motion * settings.pan_sensitivity;
         }
         if settings.scroll_action == Some(PanOrbitAction::Pan) {
@@ -158,9 +222,10 @@ fn pan_orbit_camera(
             total_pan -= total_scroll_pixels
                 * settings.scroll_pixel_sensitivity * settings.pan_sensitivity;
         }
+        total_pan -= right_stick * stick_motion * settings.pan_sensitivity;
 
         let mut total_orbit = Vec2::ZERO;
-        if settings.orbit_key.map(|key| kbd.pressed(key)).unwrap_or(false) {
+        if actions.pressed(Action::Orbit) {
             total_orbit -= total_motion * settings.orbit_sensitivity;
         }
         if settings.scroll_action == Some(PanOrbitAction::Orbit) {
@@ -169,9 +234,11 @@ fn pan_orbit_camera(
             total_orbit -= total_scroll_pixels
                 * settings.scroll_pixel_sensitivity * settings.orbit_sensitivity;
         }
+        // Pushing the stick up orbits like dragging the mouse up
+        total_orbit -= left_stick * stick_motion * settings.orbit_sensitivity;
 
         let mut total_zoom = Vec2::ZERO;
-        if settings.zoom_key.map(|key| kbd.pressed(key)).unwrap_or(false) {
+        if actions.pressed(Action::Zoom) {
             total_zoom -= total_motion * settings.zoom_sensitivity;
         }
         if settings.scroll_action == Some(PanOrbitAction::Zoom) {
@@ -180,10 +247,11 @@ fn pan_orbit_camera(
             total_zoom -= total_scroll_pixels
                 * settings.scroll_pixel_sensitivity * settings.zoom_sensitivity;
         }
+        total_zoom.y -= total_trigger * trigger_motion * settings.zoom_sensitivity;
 
         // Upon starting a new orbit maneuver (key is just pressed),
         // check if we are starting it upside-down
-        if settings.orbit_key.map(|key| kbd.just_pressed(key)).unwrap_or(false) {
+        if actions.just_pressed(Action::Orbit) || stick_orbit_started {
             state.upside_down = state.pitch < -FRAC_PI_2 || state.pitch > FRAC_PI_2;
         }
 
@@ -261,6 +329,8 @@ fn pan_orbit_camera(
     Constants
 */
 
+//VIRT_ZOOM, SIZE_PARTICLE, LIGHT_STRENGTH, GIZMOS_AXES_LENGTH, CAMERA_FOV and SIM_DT are only defaults,
+//assets/settings.ron can change them while the app runs - see settings.rs
 pub const VIRT_ZOOM: f64 = 10.0;
 
 pub const COLOR_PARTICLE: Color = Color::Srgba(WHITE_SMOKE);
@@ -273,28 +343,144 @@ pub const GIZMOS_AXES_LENGTH: f32 = 100.0;
 pub const CAMERA_FOV: f32 = 1.2;
 
 pub const SIM_DT: f64 = 0.001;
+//fewer particles than this per task isn't worth sending to another thread
+pub const MIN_CHUNK: usize = 256;
 
 /*
     Resources
 */
 
+//every system that can be picked - see registry.rs
+#[derive(Resource, Default)]
+pub struct SystemRegistry(pub registry::Registry);
+
 #[derive(Resource)]
 struct ChaosEquationResource {
-    pub eq: chaos::ChaosEq, 
+    //index into the SystemRegistry
+    pub system: usize,
+    //current parameter values of that system
+    pub params: Vec<f64>,
+    //index into integrators::INTEGRATORS
+    pub integrator: usize,
     pub steps: u8,
     pub dt_mult: f32,
+    //dt before dt_mult scales it, from the settings
+    pub sim_dt: f64,
+    //no stepping, except for the one FixedUpdate after frame_step gets set
+    pub paused: bool,
+    pub frame_step: bool,
+    //integrating backwards, with dt negated
+    pub reversed: bool,
+}
+
+impl ChaosEquationResource {
+    //switch to another system, starting from its default parameters
+    pub fn select(&mut self, system: usize, registry: &registry::Registry) {
+        self.system = system;
+        self.params = registry.get(system).default_params();
+    }
+
+    //how far one step moves, in simulated time
+    pub fn dt(&self) -> f64 {
+        return self.sim_dt * (f32::powf(2.0, self.dt_mult)) as f64;
+    }
+
+    //dt in the direction time runs - dt() itself stays positive for whatever measures the system instead of moving it
+    pub fn step_dt(&self) -> f64 {
+        return if self.reversed { -self.dt() } else { self.dt() };
+    }
+}
+
+//for everything that moves particles along (or watches them move) in FixedUpdate
+fn simulation_running(eq: Res<ChaosEquationResource>) -> bool {
+    return !eq.paused || eq.frame_step;
+}
+
+//the one step has been taken
+fn end_frame_step(mut eq: ResMut<ChaosEquationResource>) {
+    if eq.frame_step {
+        eq.frame_step = false;
+    }
 }
 
 impl FromWorld for ChaosEquationResource {
-    fn from_world(_world: &mut World) -> Self {
+    fn from_world(world: &mut World) -> Self {
+        let registry = &world.resource::<SystemRegistry>().0;
+        let system = registry.find("lorenz").unwrap_or(0);
         return ChaosEquationResource {
-            eq: chaos_equations::lorenz_attractor_standard,
+            system,
+            params: registry.get(system).default_params(),
+            integrator: 0,
             steps: 1,
             dt_mult: 2.5,
+            sim_dt: SIM_DT,
+            paused: false,
+            frame_step: false,
+            reversed: false,
         };
     }
 }
 
+//the system the camera and dt were last fitted to - see frame_selected_system
+#[derive(Resource, Default)]
+pub struct FramedSystem(pub Option<usize>);
+
+//what was given on the command line
+#[derive(Resource, Default)]
+pub struct LaunchOptions {
+    //snapshot to load on startup
+    pub load: Option<std::path::PathBuf>,
+    //seed for SimRng, instead of a random one
+    pub seed: Option<u64>,
+    //typed systems to load, instead of equations.ron
+    pub equations: Option<std::path::PathBuf>,
+}
+
+impl LaunchOptions {
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        let mut options = LaunchOptions::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--load" => options.load = args.next().map(Into::into),
+                "--equations" => options.equations = args.next().map(Into::into),
+                "--seed" => match args.next().map(|s| s.parse()) {
+                    Some(Ok(seed)) => options.seed = Some(seed),
+                    _ => eprintln!("--seed needs a whole number"),
+                },
+                //no logger yet at this point
+                _ => eprintln!("ignoring unknown argument `{arg}`"),
+            }
+        }
+        return options;
+    }
+}
+
+//every random number the simulation uses comes from here, so a seed replays a session's spawns exactly
+#[derive(Resource)]
+pub struct SimRng {
+    pub seed: u64,
+    pub rng: spawn::SpawnRng,
+}
+
+impl SimRng {
+    pub fn new(seed: u64) -> Self {
+        return SimRng { seed, rng: spawn::rng_from_seed(seed) };
+    }
+}
+
+impl FromWorld for SimRng {
+    fn from_world(world: &mut World) -> Self {
+        let seed = world.get_resource::<LaunchOptions>()
+            .and_then(|o| o.seed)
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        return SimRng::new(seed);
+    }
+}
+
+//which parameter of the current system the arrow keys are editing
+#[derive(Resource, Default)]
+pub struct ParamSelection(pub usize);
+
 #[derive(Resource)]
 pub struct CubeMeshMaterial(pub Handle<Mesh>, pub Handle<StandardMaterial>);
 
@@ -328,9 +514,6 @@ impl FromWorld for ParticleCount {
     }
 }
 
-// #[derive(Resource)]
-// pub struct SimSettings;
-
 /*
     Components
 */
@@ -338,9 +521,28 @@ impl FromWorld for ParticleCount {
 #[derive(Component)]
 enum DisplayText {
     Fps,
+    System,
+    Prompt,
     ParticleCount,
+    Playback,
     DeltaTime,
+    Lyapunov,
+    Divergence,
+    Section,
+    Bifurcation,
+    FixedPoints,
     StepsPerFrame,
+    Integrator,
+    Trails,
+    Coloring,
+    RenderMode,
+    Recording,
+    Snapshot,
+    Seed,
+    Spawn,
+    Keys,
+    //multi-line, keep it last
+    Params,
 }
 
 #[derive(Component)]
@@ -356,9 +558,9 @@ pub struct ParticleBundle {
     pbr_bundle: PbrBundle,
 }
 impl ParticleBundle {
-    pub fn from_world_xy(x: f32, y: f32, asset: &CubeMeshMaterial)->Self {
+    pub fn from_world_xy(x: f32, y: f32, asset: &CubeMeshMaterial, settings: &SimSettings)->Self {
         return Self {
-            particle: Particle(world_to_virt_coord(x, y, 0.0)),
+            particle: Particle(settings.world_to_virt(Vec3::new(x, y, 0.0))),
             pbr_bundle: PbrBundle {
                 mesh: asset.0.clone(),
                 material: asset.1.clone(),
@@ -367,42 +569,89 @@ impl ParticleBundle {
             }
         };
     }
-    pub fn from_coord(c: chaos::Coord, asset: &CubeMeshMaterial)->Self {
+    pub fn from_coord(c: chaos::Coord, asset: &CubeMeshMaterial, settings: &SimSettings)->Self {
         return Self {
             particle: Particle(c),
             pbr_bundle: PbrBundle {
                 mesh: asset.0.clone(),
                 material: asset.1.clone(),
-                transform: virt_to_world(&c),
+                transform: settings.virt_to_world(&c),
                 ..default()
             }
         };
     }
 }
 
+//spawn a particle the way the current render mode wants it - point cloud particles have no mesh of their own
+fn spawn_particle<'a>(
+    cmd: &'a mut Commands,
+    c: chaos::Coord,
+    mode: point_cloud::RenderMode,
+    asset: &CubeMeshMaterial,
+    settings: &SimSettings,
+) -> bevy::ecs::system::EntityCommands<'a> {
+    return match mode {
+        point_cloud::RenderMode::Cubes => cmd.spawn(ParticleBundle::from_coord(c, asset, settings)),
+        point_cloud::RenderMode::Points => cmd.spawn(Particle(c)),
+    };
+}
+
 /*
     Systems
 */
 
-fn draw_axes(mut gizmos: Gizmos) {
-    gizmos.axes(Transform::default(), GIZMOS_AXES_LENGTH);
+fn draw_axes(mut gizmos: Gizmos, sim_settings: Res<SimSettings>) {
+    gizmos.axes(Transform::default(), sim_settings.gizmos_axes_length);
+}
+
+//what spawning a particle from a system takes, counting it included
+#[derive(SystemParam)]
+pub struct Spawner<'w, 's> {
+    cmd: Commands<'w, 's>,
+    asset: Res<'w, CubeMeshMaterial>,
+    render_mode: Res<'w, point_cloud::RenderMode>,
+    sim_settings: Res<'w, SimSettings>,
+    pc: ResMut<'w, ParticleCount>,
+}
+
+impl Spawner<'_, '_> {
+    pub fn spawn(&mut self, c: chaos::Coord) -> Entity {
+        let entity = spawn_particle(&mut self.cmd, c, *self.render_mode, &self.asset, &self.sim_settings).id();
+        self.pc.0 += 1;
+        return entity;
+    }
+
+    //one particle at a point in the world, or a bunch around it
+    pub fn spawn_at_world(&mut self, at: Vec3, bunch: Option<(&spawn::Shape, &mut SimRng)>) {
+        let at = self.sim_settings.world_to_virt(at);
+        match bunch {
+            Some((shape, rng)) => {
+                for c in shape.generate(&mut rng.rng, at) {
+                    self.spawn(c);
+                }
+            }
+            None => {
+                self.spawn(at);
+            }
+        }
+    }
 }
 
 fn mouse_click_system(
-    mut cmd: Commands, 
+    mut spawner: Spawner,
     q_window: Query<&Window, With<PrimaryWindow>>,
-    cube_mesh_material: Res<CubeMeshMaterial>,
     q_camera: Query<(
         &PanOrbitState,
         &Transform,
     )>,
-    mut pc: ResMut<ParticleCount>,
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
+    mut rng: ResMut<SimRng>,
+    spawn_settings: Res<spawning::SpawnSettings>,
+    sim_settings: Res<SimSettings>,
 ) {
-    let bunch_spawn = keys.pressed(KeyCode::ShiftLeft);
+    let bunch_spawn = actions.pressed(Action::SpawnBunch);
     let window = q_window.single();
-    let mut rng = rand::thread_rng();
-    
+
     for (state, transform) in &q_camera {
 
         if let Some(world_position) = window.cursor_position() {
@@ -410,125 +659,290 @@ fn mouse_click_system(
             let half_width = window.width()/2.0;
             let px = (world_position.x / half_width) - 1.0;
             let py = (world_position.y / half_height) - 1.0;
-            let dx = state.radius * (CAMERA_FOV / 2.0).tan() * px * (half_width/half_height);
-            let dy = state.radius * (CAMERA_FOV / 2.0).tan() * py; //fov is vertical AND half of CAMERA_FOV (half screen, it's weird)
+            let fov = sim_settings.camera_fov;
+            let dx = state.radius * (fov / 2.0).tan() * px * (half_width/half_height);
+            let dy = state.radius * (fov / 2.0).tan() * py; //fov is vertical AND half of the camera's fov (half screen, it's weird)
             let spawn_at = state.center + transform.down()*dy + transform.right()*dx;
-            if bunch_spawn {
-                for _ in 0..20 {
-                    // cmd.spawn(ParticleBundle::from_coord(world_to_virt_coord(2.0, 1.0, 1.0)));
-                    let dx = rng.gen::<f64>().fract();
-                    let dy = rng.gen::<f64>().fract();
-                    let dz = rng.gen::<f64>().fract();
-                    cmd.spawn(ParticleBundle::from_coord(
-                        chaos::Coord{x:dx,y:dy,z:dz} + world_to_virt_coord(spawn_at.x, spawn_at.y, spawn_at.z),
-                        &cube_mesh_material
-                    ));
-                }
-                pc.0 += 20;
-            } else {
-                cmd.spawn(ParticleBundle::from_coord(world_to_virt_coord(spawn_at.x, spawn_at.y, spawn_at.z), &cube_mesh_material));
-                pc.0 += 1;
-            }
+            spawner.spawn_at_world(spawn_at, bunch_spawn.then_some((&spawn_settings.0, &mut *rng)));
         }
     }
 }
 
-fn vmove_particle_system(mut particles: Query<&mut Particle>, chaos_eq: Res<ChaosEquationResource>) {
-    for mut particle in &mut particles {
-        for _ in 0..chaos_eq.steps {
-            particle.0 = (chaos_eq.eq)(&particle.0, SIM_DT * (f32::powf(2.0, chaos_eq.dt_mult)) as f64);
+//for gamepads, with no cursor to click with: spawn where the camera looks
+fn spawn_at_center_system(
+    mut spawner: Spawner,
+    q_camera: Query<&PanOrbitState>,
+    actions: Actions,
+    mut rng: ResMut<SimRng>,
+    spawn_settings: Res<spawning::SpawnSettings>,
+) {
+    if !actions.just_pressed(Action::SpawnParticle) {
+        return;
+    }
+    let bunch_spawn = actions.pressed(Action::SpawnBunch);
+    for state in &q_camera {
+        spawner.spawn_at_world(state.center, bunch_spawn.then_some((&spawn_settings.0, &mut *rng)));
+    }
+}
+
+//copy the particles into an ensemble, step that, and copy them back
+//the copies are cheap next to the stepping, which this way runs four particles at a time
+fn vmove_particle_system(
+    mut particles: Query<(&mut Particle, Option<&recording::ParticleId>)>,
+    chaos_eq: Res<ChaosEquationResource>,
+    registry: Res<SystemRegistry>,
+    mut section: ResMut<section::Section>,
+    mut ensemble: Local<Ensemble>,
+    mut before: Local<Ensemble>,
+    mut ids: Local<Vec<Option<u64>>>,
+) {
+    let integrator = integrators::INTEGRATORS[chaos_eq.integrator];
+    let system = registry.0.get(chaos_eq.system);
+    let dt = chaos_eq.step_dt();
+    let steps = chaos_eq.steps as usize;
+
+    ensemble.clear();
+    ids.clear();
+    for (particle, id) in &particles {
+        ensemble.push(particle.0);
+        ids.push(id.map(|id| id.0));
+    }
+    //a poincaré section has to look between every two steps, otherwise all of them go in one go
+    let (passes, steps_per_pass) = if section.enabled { (steps, 1) } else { (1, steps) };
+    //every particle is independent, so spread them over all cores
+    let pool = ComputeTaskPool::get();
+    let chunk = ensemble.len().div_ceil(pool.thread_num()).max(MIN_CHUNK);
+    for _ in 0..passes {
+        if section.enabled {
+            before.clone_from(&ensemble);
+        }
+        pool.scope(|scope| {
+            for mut piece in ensemble.chunks_mut(chunk) {
+                let params = &chaos_eq.params;
+                scope.spawn(async move { piece.step(system, params, integrator, dt, steps_per_pass) });
+            }
+        });
+        if section.enabled {
+            section.record(&before, &ensemble, &ids, dt);
         }
     }
+    for (i, (mut particle, _)) in particles.iter_mut().enumerate() {
+        particle.0 = ensemble.get(i);
+    }
 }
 
-fn transform_particle_system(mut particles: Query<(&Particle, &mut Transform)>) {
+fn transform_particle_system(mut particles: Query<(&Particle, &mut Transform)>, sim_settings: Res<SimSettings>) {
     for (particle, mut transform) in &mut particles {
-        virt_to_world_mut(&particle.0, &mut transform);
+        sim_settings.virt_to_world_mut(&particle.0, &mut transform);
     }
 }
 
 fn init_lighting(mut cmd: Commands) {
     // ambient light
     cmd.insert_resource(AmbientLight {
-        color: LIGHT_COLOR.into(),
+        color: LIGHT_COLOR,
         brightness: LIGHT_STRENGTH,
     });
 }
 
 fn init_text(mut cmd: Commands) {
-    cmd.spawn((DisplayText::Fps, TextBundle {
-        text: Text::from_section("--", TextStyle::default()),
-        style: Style {
-            position_type: PositionType::Absolute,
-            top: Val::Px(5.0),
-            left: Val::Px(5.0),
-            ..default()
-        },
-        ..default()
-    }));
-    cmd.spawn((DisplayText::ParticleCount, TextBundle {
-        text: Text::from_section("--", TextStyle::default()),
-        style: Style {
-            position_type: PositionType::Absolute,
-            top: Val::Px(25.0),
-            left: Val::Px(5.0),
-            ..default()
-        },
-        ..default()
-    }));
-    cmd.spawn((DisplayText::DeltaTime, TextBundle {
-        text: Text::from_section("--", TextStyle::default()),
-        style: Style {
-            position_type: PositionType::Absolute,
-            top: Val::Px(45.0),
-            left: Val::Px(5.0),
-            ..default()
-        },
-        ..default()
-    }));
-    cmd.spawn((DisplayText::StepsPerFrame, TextBundle {
-        text: Text::from_section("--", TextStyle::default()),
-        style: Style {
-            position_type: PositionType::Absolute,
-            top: Val::Px(65.0),
-            left: Val::Px(5.0),
+    let rows = [
+        DisplayText::Fps,
+        DisplayText::System,
+        DisplayText::Prompt,
+        DisplayText::ParticleCount,
+        DisplayText::Playback,
+        DisplayText::DeltaTime,
+        DisplayText::Lyapunov,
+        DisplayText::Divergence,
+        DisplayText::Section,
+        DisplayText::Bifurcation,
+        DisplayText::FixedPoints,
+        DisplayText::StepsPerFrame,
+        DisplayText::Integrator,
+        DisplayText::Trails,
+        DisplayText::Coloring,
+        DisplayText::RenderMode,
+        DisplayText::Recording,
+        DisplayText::Snapshot,
+        DisplayText::Seed,
+        DisplayText::Spawn,
+        DisplayText::Keys,
+        DisplayText::Params,
+    ];
+    for (i, row) in rows.into_iter().enumerate() {
+        cmd.spawn((row, TextBundle {
+            text: Text::from_section("--", TextStyle::default()),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(5.0 + 20.0 * i as f32),
+                left: Val::Px(5.0),
+                ..default()
+            },
             ..default()
-        },
-        ..default()
-    }));
+        }));
+    }
 }
 
-fn keybind_listener(mut cmd: Commands, keys: Res<ButtonInput<KeyCode>>, rem_particles: Res<OneShotSystems>, mut eq: ResMut<ChaosEquationResource>) {
-    if keys.just_pressed(KeyCode::KeyC) {
+//the equation actions pick the first nine systems in the registry, next/previous cycle through all of them
+fn keybind_listener(mut cmd: Commands, actions: Actions, rem_particles: Res<OneShotSystems>, mut eq: ResMut<ChaosEquationResource>, registry: Res<SystemRegistry>) {
+    if actions.just_pressed(Action::ClearParticles) {
         cmd.run_system(rem_particles.despawn_particles);
     }
-    if keys.just_pressed(KeyCode::Digit1) {
-        eq.eq = chaos::basic_equation;
-    } else if keys.just_pressed(KeyCode::Digit2) {
-        eq.eq = chaos::lorenz_attractor_standard;
-    } else if keys.just_pressed(KeyCode::Digit3) {
-        eq.eq = chaos::rossler_attractor_variant1;
-    } else if keys.just_pressed(KeyCode::Digit4) {
-        eq.eq = chaos::rossler_attractor_variant2;
+    let registry = &registry.0;
+    for (i, action) in keybindings::EQUATION_ACTIONS.into_iter().enumerate().take(registry.len()) {
+        if actions.just_pressed(action) {
+            eq.select(i, registry);
+        }
+    }
+    if actions.just_pressed(Action::NextEquation) {
+        let next = (eq.system + 1) % registry.len();
+        eq.select(next, registry);
+    } else if actions.just_pressed(Action::PreviousEquation) {
+        let previous = (eq.system + registry.len() - 1) % registry.len();
+        eq.select(previous, registry);
+    }
+
+    if actions.just_pressed(Action::NextIntegrator) {
+        eq.integrator = (eq.integrator + 1) % integrators::INTEGRATORS.len();
     }
 
-    if keys.just_pressed(KeyCode::NumpadAdd) {
+    if actions.just_pressed(Action::StepsUp) {
         eq.steps += 1;
-    } else if keys.just_pressed(KeyCode::NumpadSubtract) {
-        if eq.steps != 0 {
-            eq.steps -= 1;
-        }
+    } else if actions.just_pressed(Action::StepsDown) {
+        eq.steps = eq.steps.saturating_sub(1);
     }
 
-    if keys.just_pressed(KeyCode::BracketRight) {
+    if actions.just_pressed(Action::DtUp) {
         eq.dt_mult += 0.2;
-    } else if keys.just_pressed(KeyCode::BracketLeft) {
+    } else if actions.just_pressed(Action::DtDown) {
         eq.dt_mult -= 0.2;
     }
+
+    //stepping a frame pauses first, if it's running
+    if actions.just_pressed(Action::TogglePause) {
+        eq.paused = !eq.paused;
+    } else if actions.just_pressed(Action::StepFrame) {
+        (eq.paused, eq.frame_step) = (true, true);
+    }
+    if actions.just_pressed(Action::Reverse) {
+        eq.reversed = !eq.reversed;
+    }
+}
+
+//a new random seed, or the current one started over - the next spawns repeat the ones since it was set
+fn seed_keybinds(actions: Actions, mut rng: ResMut<SimRng>) {
+    let seed = if actions.just_pressed(Action::RestartSeed) {
+        rng.seed
+    } else if actions.just_pressed(Action::NewSeed) {
+        rand::thread_rng().gen()
+    } else {
+        return;
+    };
+    *rng = SimRng::new(seed);
+}
+
+//when a different system gets picked, jump to its recommended dt and point the camera at it
+//(leaves the startup view alone, only reacts to switching)
+fn frame_selected_system(
+    mut last: ResMut<FramedSystem>,
+    mut eq: ResMut<ChaosEquationResource>,
+    registry: Res<SystemRegistry>,
+    sim_settings: Res<SimSettings>,
+    mut q_camera: Query<(&mut PanOrbitState, &mut Transform)>,
+) {
+    if last.0 == Some(eq.system) {
+        return;
+    }
+    let first_run = last.0.is_none();
+    last.0 = Some(eq.system);
+    if first_run {
+        return;
+    }
+
+    let system = registry.0.get(eq.system);
+    eq.dt_mult = (system.dt / eq.sim_dt).log2() as f32;
+    for (mut state, mut transform) in &mut q_camera {
+        state.center = sim_settings.virt_to_world(&system.framing.center).translation;
+        state.radius = (system.framing.radius * sim_settings.virt_zoom) as f32;
+        transform.translation = state.center + transform.back() * state.radius;
+    }
+}
+
+//fraction of a parameter's range that holding an arrow key sweeps through every second
+const PARAM_SWEEP_SPEED: f64 = 0.05;
+const PARAM_SWEEP_SPEED_FINE: f64 = 0.002;
+
+//pick a parameter, hold to slide it (slowly with the slow action held too), or reset all of them
+fn param_editor_system(
+    actions: Actions,
+    time: Res<Time>,
+    mut eq: ResMut<ChaosEquationResource>,
+    registry: Res<SystemRegistry>,
+    mut selection: ResMut<ParamSelection>,
+) {
+    let params = registry.0.get(eq.system).params;
+    if params.is_empty() {
+        return;
+    }
+    if selection.0 >= params.len() {
+        selection.0 = 0;
+    }
+
+    if actions.just_pressed(Action::NextParam) {
+        selection.0 = (selection.0 + 1) % params.len();
+    } else if actions.just_pressed(Action::PreviousParam) {
+        selection.0 = (selection.0 + params.len() - 1) % params.len();
+    }
+
+    if actions.just_pressed(Action::ResetParams) {
+        eq.params = registry.0.get(eq.system).default_params();
+        return;
+    }
+
+    let mut direction = 0.0;
+    if actions.pressed(Action::ParamUp) {
+        direction += 1.0;
+    }
+    if actions.pressed(Action::ParamDown) {
+        direction -= 1.0;
+    }
+    if direction != 0.0 {
+        let def = &params[selection.0];
+        let speed = if actions.pressed(Action::SlowParam) { PARAM_SWEEP_SPEED_FINE } else { PARAM_SWEEP_SPEED };
+        let delta = direction * speed * (def.max - def.min) * time.delta_seconds_f64();
+        let value = eq.params[selection.0] + delta;
+        eq.params[selection.0] = def.clamp(value);
+    }
 }
 
 //some more stolen code for displaying fps
-fn display_stats(diagnostics: Res<DiagnosticsStore>, mut dtexts: Query<(&mut Text, &DisplayText)>, pc: Res<ParticleCount>, eq: Res<ChaosEquationResource>) {
+//everything the overlay reads, bundled up to keep display_stats' signature sane
+#[derive(SystemParam)]
+struct OverlaySources<'w> {
+    pc: Res<'w, ParticleCount>,
+    eq: Res<'w, ChaosEquationResource>,
+    registry: Res<'w, SystemRegistry>,
+    selection: Res<'w, ParamSelection>,
+    trail_settings: Res<'w, trails::TrailSettings>,
+    color_settings: Res<'w, coloring::ColorSettings>,
+    render_mode: Res<'w, point_cloud::RenderMode>,
+    recording: Res<'w, recording::Recording>,
+    snapshot_status: Res<'w, saving::SnapshotStatus>,
+    rng: Res<'w, SimRng>,
+    spawn_settings: Res<'w, spawning::SpawnSettings>,
+    lyapunov: Res<'w, sensitivity::LiveLyapunov>,
+    divergence: Res<'w, sensitivity::Divergence>,
+    section: Res<'w, section::Section>,
+    bifurcation: Res<'w, sweeping::Bifurcation>,
+    equilibria: Res<'w, equilibria::Equilibria>,
+    prompt: Res<'w, prompt::EquationPrompt>,
+    input_map: Res<'w, keybindings::InputMap>,
+    keybinding_panel: Res<'w, keybindings::KeybindingPanel>,
+}
+
+fn display_stats(diagnostics: Res<DiagnosticsStore>, mut dtexts: Query<(&mut Text, &DisplayText)>, sources: OverlaySources) {
+    let OverlaySources { pc, eq, registry, selection, trail_settings, color_settings, render_mode, recording, snapshot_status, rng, spawn_settings, lyapunov, divergence, section, bifurcation, equilibria, prompt, input_map, keybinding_panel } = sources;
     for (mut text, text_type) in &mut dtexts {
         match text_type {
             DisplayText::Fps => {
@@ -572,10 +986,20 @@ fn display_stats(diagnostics: Res<DiagnosticsStore>, mut dtexts: Query<(&mut Tex
                     text.sections[0].style.color = Color::WHITE;
                 }
             }
+            DisplayText::System => {
+                text.sections[0].value = registry.0.get(eq.system).name.to_string();
+            }
             DisplayText::ParticleCount => {
                 let pnum = pc.0;
                 text.sections[0].value = format!("{} particles", pnum);
             }
+            DisplayText::Playback => {
+                let direction = if eq.reversed { "backwards" } else { "forwards" };
+                let state = if eq.paused { "paused" } else { "running" };
+                //the error of a low order integrator gets blown up on the way back, and the particles never make it home
+                let hint = if eq.reversed && integrators::INTEGRATORS[eq.integrator].order() < 4 { " (use rk4 or better to retrace)" } else { "" };
+                text.sections[0].value = format!("{state}, {direction}{hint}");
+            }
             DisplayText::StepsPerFrame => {
                 let pnum = eq.steps;
                 text.sections[0].value = format!("{} steps per frame", pnum);
@@ -584,6 +1008,123 @@ fn display_stats(diagnostics: Res<DiagnosticsStore>, mut dtexts: Query<(&mut Tex
                 let pnum = f32::powf(2.0, eq.dt_mult);
                 text.sections[0].value = format!("dt={pnum:.2}", );
             }
+            DisplayText::Lyapunov => {
+                text.sections[0].value = match &lyapunov.estimate {
+                    None => "lyapunov: -".to_string(),
+                    Some(l) if !l.is_finite() => "lyapunov: diverged".to_string(),
+                    Some(_) if lyapunov.settling => "lyapunov: settling".to_string(),
+                    Some(l) => {
+                        let [l1, l2, l3] = l.exponents();
+                        format!("lyapunov: {l1:.3} {l2:.3} {l3:.3} (over t={:.0})", l.time)
+                    }
+                };
+            }
+            DisplayText::Divergence => {
+                text.sections[0].value = match (divergence.samples.last(), divergence.fit) {
+                    _ if divergence.twins.is_none() => "twins: off".to_string(),
+                    (Some(&(_, ln_d)), Some((slope, _))) => {
+                        let saturated = if divergence.fitted < divergence.samples.len() { ", saturated" } else { "" };
+                        format!("twins: ln d = {ln_d:.1}, slope {slope:.3}{saturated}")
+                    }
+                    _ => "twins: starting".to_string(),
+                };
+            }
+            DisplayText::Section => {
+                text.sections[0].value = match &section.status {
+                    _ if !section.enabled => "section: off".to_string(),
+                    Some(status) => format!("section: {}, {} crossings - {status}", section.plane, section.points.len()),
+                    None => format!("section: {}, {} crossings", section.plane, section.points.len()),
+                };
+            }
+            DisplayText::Bifurcation => {
+                let done = bifurcation.done.load(std::sync::atomic::Ordering::Relaxed);
+                text.sections[0].value = match &bifurcation.status {
+                    _ if bifurcation.is_running() => format!(
+                        "bifurcation: {}%", done * 100 / sweeping::BIFURCATION_COLUMNS,
+                    ),
+                    Some(status) => format!("bifurcation ({} max): {status}", bifurcation.axis.name()),
+                    None => format!("bifurcation: {} max", bifurcation.axis.name()),
+                };
+            }
+            DisplayText::Prompt => {
+                text.sections[0].value = match (&prompt.text, &prompt.status) {
+                    (Some(typed), Some(error)) => format!("> {typed}_ ({error})"),
+                    (Some(typed), None) => format!("> {typed}_"),
+                    (None, Some(status)) => format!("typed equations: {status}"),
+                    (None, None) => format!("typed equations: {} to type one in", input_map.describe(Action::TypeEquation)),
+                };
+            }
+            DisplayText::FixedPoints => {
+                text.sections[0].value = match equilibria.enabled {
+                    true => format!("fixed points: {}", equilibria.summary()),
+                    false => "fixed points: hidden".to_string(),
+                };
+            }
+            DisplayText::Integrator => {
+                let name = integrators::INTEGRATORS[eq.integrator].name();
+                text.sections[0].value = format!("{} integrator", name);
+            }
+            DisplayText::Trails => {
+                text.sections[0].value = if trail_settings.enabled {
+                    format!("trails: {} steps, fade {:.2}", trail_settings.length, trail_settings.fade)
+                } else {
+                    "trails: off".to_string()
+                };
+            }
+            DisplayText::Coloring => {
+                text.sections[0].value = if color_settings.mode == coloring::ColorMode::Uniform {
+                    "color: uniform".to_string()
+                } else {
+                    format!("color: {} ({})", color_settings.mode.name(), color_settings.colormap.name())
+                };
+            }
+            DisplayText::RenderMode => {
+                text.sections[0].value = format!("drawn as {}", render_mode.name());
+            }
+            DisplayText::Recording => {
+                text.sections[0].value = match (&recording.path, &recording.error) {
+                    _ if recording.is_recording() => format!(
+                        "recording to {} ({} rows)",
+                        recording.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+                        recording.rows,
+                    ),
+                    (_, Some(error)) => format!("not recording ({}) - {error}", recording.format.name()),
+                    (Some(path), None) => format!("not recording ({}), last: {}", recording.format.name(), path.display()),
+                    (None, None) => format!("not recording ({})", recording.format.name()),
+                };
+            }
+            DisplayText::Snapshot => {
+                text.sections[0].value = snapshot_status.0.clone().unwrap_or_else(|| "f5 saves, f9 loads".to_string());
+            }
+            DisplayText::Seed => {
+                text.sections[0].value = format!("seed {}", rng.seed);
+            }
+            DisplayText::Spawn => {
+                let shape = &spawn_settings.0;
+                text.sections[0].value = if shape.pattern == spawn::Pattern::Pair {
+                    format!("spawn: pair {:e} apart", spawn::PAIR_SEPARATION)
+                } else {
+                    format!("spawn: {} x{} r {}", shape.pattern.name(), shape.count, shape.radius)
+                };
+            }
+            DisplayText::Keys => {
+                text.sections[0].value = keybinding_panel.summary(&input_map);
+            }
+            DisplayText::Params => {
+                //one line per parameter, drawn as a little slider, the selected one highlighted
+                let params = registry.0.get(eq.system).params;
+                text.sections = params.iter().zip(&eq.params).enumerate().map(|(i, (def, value))| {
+                    let filled = (def.fraction(*value) * 20.0).round() as usize;
+                    let style = TextStyle {
+                        color: if i == selection.0 { Color::Srgba(YELLOW) } else { Color::WHITE },
+                        ..default()
+                    };
+                    TextSection::new(
+                        format!("{} [{}{}] {:.4}\n", def.name, "|".repeat(filled), ".".repeat(20 - filled), value),
+                        style,
+                    )
+                }).collect();
+            }
         }
     }
 }
@@ -608,53 +1149,55 @@ pub struct ChaosPlugin;
 impl Plugin for ChaosPlugin {
     fn build(&self, app: &mut App) {
         app
+            .init_resource::<SystemRegistry>()
             .init_resource::<ChaosEquationResource>()
             .init_resource::<CubeMeshMaterial>()
             .init_resource::<OneShotSystems>()
             .init_resource::<ParticleCount>()
+            .init_resource::<ParamSelection>()
+            .init_resource::<FramedSystem>()
+            .init_resource::<LaunchOptions>()
+            .init_resource::<SimRng>()
+            .add_plugins(settings::SettingsPlugin)
+            .add_plugins(keybindings::KeybindingsPlugin)
+            .add_plugins(trails::TrailsPlugin)
+            .add_plugins(coloring::ColoringPlugin)
+            .add_plugins(point_cloud::PointCloudPlugin)
+            .add_plugins(recording::RecordingPlugin)
+            .add_plugins(saving::SavingPlugin)
+            .add_plugins(spawning::SpawningPlugin)
+            .add_plugins(sensitivity::SensitivityPlugin)
+            .add_plugins(section::SectionPlugin)
+            .add_plugins(sweeping::SweepingPlugin)
+            .add_plugins(equilibria::EquilibriaPlugin)
+            .add_plugins(prompt::PromptPlugin)
             .add_systems(Startup, spawn_camera)
             .add_systems(Startup, init_lighting)
             .add_systems(Startup, init_text)
-            .add_systems(FixedUpdate, vmove_particle_system)
+            .add_systems(FixedUpdate, vmove_particle_system.run_if(simulation_running))
+            .add_systems(FixedPostUpdate, end_frame_step)
             .add_systems(Update, transform_particle_system)
             .add_systems(Update, mouse_click_system
                 .run_if(input_pressed(MouseButton::Left)))
+            .add_systems(Update, spawn_at_center_system)
             .add_systems(Update, draw_axes)
             .add_systems(Update, keybind_listener)
+            .add_systems(Update, param_editor_system)
+            .add_systems(Update, seed_keybinds)
+            .add_systems(Update, frame_selected_system.after(keybind_listener))
             .add_systems(Update, display_stats)
             .add_systems(Update, pan_orbit_camera
                     .run_if(any_with_component::<PanOrbitState>));
     }
 }
 
-/*
-    Helper functions
-*/
-
-fn world_to_virt_coord(x: f32, y: f32, z: f32)->chaos::Coord {
-    return chaos::Coord {
-        x: x as f64 / VIRT_ZOOM,
-        y: y as f64 / VIRT_ZOOM,
-        z: z as f64 / VIRT_ZOOM,
-    };
-}
-
-fn virt_to_world(c: &chaos::Coord)->Transform {
-    return Transform::from_xyz((c.x * VIRT_ZOOM) as f32, (c.y * VIRT_ZOOM) as f32, (c.z * VIRT_ZOOM) as f32);
-}
-
-fn virt_to_world_mut(c: &chaos::Coord, t: &mut Transform) {
-    t.translation.x = (c.x * VIRT_ZOOM) as f32;
-    t.translation.y = (c.y * VIRT_ZOOM) as f32;
-    t.translation.z = (c.z * VIRT_ZOOM) as f32;
-}
-
 /*
     Main
 */
 
 fn main() {
     App::new()
+        .insert_resource(LaunchOptions::from_args(std::env::args().skip(1)))
         .add_plugins((DefaultPlugins,ChaosPlugin,FrameTimeDiagnosticsPlugin))
         .run();
 }
\ No newline at end of file
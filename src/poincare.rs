@@ -0,0 +1,169 @@
+use std::fmt;
+
+use crate::chaos_equations::Coord;
+use crate::ensemble::Ensemble;
+
+/*
+    Poincaré Sections
+    a plane where one coordinate is fixed; every time a trajectory goes through it (in the chosen direction)
+    that point gets kept - the 3d flow turns into a 2d map, and the structure of the attractor shows up as curves
+    the crossing point is interpolated linearly between the two steps either side of the plane
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Axis::X => "x",
+            Axis::Y => "y",
+            Axis::Z => "z",
+        };
+    }
+
+    pub fn next(&self) -> Self {
+        let i = Axis::ALL.iter().position(|a| a == self).unwrap_or(0);
+        return Axis::ALL[(i + 1) % Axis::ALL.len()];
+    }
+
+    pub fn of(&self, c: &Coord) -> f64 {
+        return match self {
+            Axis::X => c.x,
+            Axis::Y => c.y,
+            Axis::Z => c.z,
+        };
+    }
+}
+
+//which way through the plane counts
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    //from below the value to above it
+    Rising,
+    Falling,
+    Both,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 3] = [Direction::Rising, Direction::Falling, Direction::Both];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Direction::Rising => "rising",
+            Direction::Falling => "falling",
+            Direction::Both => "both ways",
+        };
+    }
+
+    pub fn next(&self) -> Self {
+        let i = Direction::ALL.iter().position(|d| d == self).unwrap_or(0);
+        return Direction::ALL[(i + 1) % Direction::ALL.len()];
+    }
+}
+
+//`axis` = `value`, crossed `direction`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Plane {
+    pub axis: Axis,
+    pub value: f64,
+    pub direction: Direction,
+}
+
+impl Plane {
+    //how far along the step from `a` to `b` it goes through the plane, 0 to 1 - None if it doesn't, or the wrong way
+    pub fn crossing(&self, a: &Coord, b: &Coord) -> Option<f64> {
+        let (da, db) = (self.axis.of(a) - self.value, self.axis.of(b) - self.value);
+        let rising = da < 0.0 && db >= 0.0;
+        let falling = da > 0.0 && db <= 0.0;
+        let counts = match self.direction {
+            Direction::Rising => rising,
+            Direction::Falling => falling,
+            Direction::Both => rising || falling,
+        };
+        if !counts {
+            return None;
+        }
+        return Some(da / (da - db));
+    }
+
+    //the two coordinates that vary within the plane, in x, y, z order
+    pub fn project(&self, c: &Coord) -> (f64, f64) {
+        return match self.axis {
+            Axis::X => (c.y, c.z),
+            Axis::Y => (c.x, c.z),
+            Axis::Z => (c.x, c.y),
+        };
+    }
+
+    //every particle that went through the plane between two states of the same ensemble
+    //as (index, fraction of the step, point on the plane)
+    pub fn crossings<'a>(&'a self, before: &'a Ensemble, after: &'a Ensemble) -> impl Iterator<Item = (usize, f64, Coord)> + 'a {
+        return (0..before.len().min(after.len())).filter_map(move |i| {
+            let (a, b) = (before.get(i), after.get(i));
+            let f = self.crossing(&a, &b)?;
+            return Some((i, f, a + (b - a) * f));
+        });
+    }
+}
+
+//"z = 27 falling"
+impl fmt::Display for Plane {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{} = {} {}", self.axis.name(), self.value, self.direction.name());
+    }
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrators;
+    use crate::registry::Registry;
+
+    #[test]
+    fn crossing_directions() {
+        let below = Coord { x: 0.0, y: 0.0, z: 1.0 };
+        let above = Coord { x: 2.0, y: 0.0, z: 5.0 };
+        let mut plane = Plane { axis: Axis::Z, value: 2.0, direction: Direction::Rising };
+        assert_eq!(plane.crossing(&below, &above), Some(0.25));
+        assert_eq!(plane.crossing(&above, &below), None);
+        plane.direction = Direction::Falling;
+        assert_eq!(plane.crossing(&above, &below), Some(0.75));
+        assert_eq!(plane.crossing(&below, &below), None);
+        plane.direction = Direction::Both;
+        assert!(plane.crossing(&below, &above).is_some() && plane.crossing(&above, &below).is_some());
+    }
+
+    #[test]
+    fn lorenz_section_lies_on_the_plane() {
+        let registry = Registry::builtin();
+        let lorenz = registry.get(registry.find("lorenz").unwrap());
+        let params = lorenz.default_params();
+        let rk4 = integrators::find("rk4").unwrap();
+        //z = rho - 1, through both of the wings' fixed points
+        let plane = Plane { axis: Axis::Z, value: 27.0, direction: Direction::Falling };
+        let mut ensemble: Ensemble = [Coord { x: 1.0, y: 1.0, z: 1.0 }, Coord { x: -3.0, y: 2.0, z: 30.0 }].into_iter().collect();
+        let mut found = 0;
+        for _ in 0..20_000 {
+            let before = ensemble.clone();
+            ensemble.step(lorenz, &params, rk4, 0.005, 1);
+            for (_, f, at) in plane.crossings(&before, &ensemble) {
+                assert!((0.0..=1.0).contains(&f));
+                assert!((at.z - 27.0).abs() < 1e-9, "{}", at.z);
+                found += 1;
+            }
+        }
+        //a loop around a wing takes a bit under one time unit
+        assert!(found > 100, "{found}");
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use bevy::render::mesh::PrimitiveTopology;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::view::NoFrustumCulling;
+
+use crate::coloring::{ColorMode, ColorSettings, Shade};
+use crate::keybindings::{Action, Actions};
+use crate::settings::SimSettings;
+use crate::{CubeMeshMaterial, Particle, COLOR_PARTICLE};
+
+/*
+    Point Cloud Rendering
+    instead of one cube entity per particle, every particle becomes one vertex of a single point list mesh
+    that gets rebuilt every frame - one draw call no matter how many particles there are
+*/
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RenderMode {
+    //one PbrBundle per particle
+    #[default]
+    Cubes,
+    //every particle is a pixel of the shared point cloud
+    Points,
+}
+
+impl RenderMode {
+    pub fn name(&self) -> &'static str {
+        return match self {
+            RenderMode::Cubes => "cubes",
+            RenderMode::Points => "points",
+        };
+    }
+}
+
+//the entity holding the point cloud mesh
+#[derive(Component)]
+pub struct PointCloud;
+
+fn init_point_cloud(
+    mut cmd: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = Mesh::new(PrimitiveTopology::PointList, RenderAssetUsages::default());
+    let material = StandardMaterial {
+        base_color: Color::WHITE,
+        unlit: true,
+        ..default()
+    };
+    cmd.spawn((
+        PointCloud,
+        PbrBundle {
+            mesh: meshes.add(mesh),
+            material: materials.add(material),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        //the aabb would be computed once for an empty mesh and never again
+        NoFrustumCulling,
+    ));
+}
+
+//add or strip the per-particle cubes when the render mode changes
+fn sync_render_mode(
+    mut cmd: Commands,
+    mode: Res<RenderMode>,
+    asset: Res<CubeMeshMaterial>,
+    sim_settings: Res<SimSettings>,
+    cubes: Query<Entity, (With<Particle>, With<Handle<Mesh>>)>,
+    bare: Query<(Entity, &Particle), Without<Handle<Mesh>>>,
+) {
+    if !mode.is_changed() {
+        return;
+    }
+    match *mode {
+        RenderMode::Points => {
+            for entity in &cubes {
+                cmd.entity(entity).remove::<PbrBundle>();
+            }
+        }
+        RenderMode::Cubes => {
+            for (entity, particle) in &bare {
+                cmd.entity(entity).insert(PbrBundle {
+                    mesh: asset.0.clone(),
+                    material: asset.1.clone(),
+                    transform: sim_settings.virt_to_world(&particle.0),
+                    ..default()
+                });
+            }
+        }
+    }
+}
+
+fn update_point_cloud(
+    mode: Res<RenderMode>,
+    color_settings: Res<ColorSettings>,
+    sim_settings: Res<SimSettings>,
+    particles: Query<(&Particle, Option<&Shade>)>,
+    mut q_cloud: Query<(&Handle<Mesh>, &mut Visibility), With<PointCloud>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Ok((handle, mut visibility)) = q_cloud.get_single_mut() else {
+        return;
+    };
+    //an empty vertex buffer isn't something we want to hand to the gpu
+    let show = *mode == RenderMode::Points && !particles.is_empty();
+    let wanted = if show { Visibility::Visible } else { Visibility::Hidden };
+    if *visibility != wanted {
+        *visibility = wanted;
+    }
+    if !show {
+        return;
+    }
+    let Some(mesh) = meshes.get_mut(handle) else {
+        return;
+    };
+
+    let uniform = COLOR_PARTICLE.to_linear().to_f32_array();
+    let mut positions = Vec::with_capacity(particles.iter().len());
+    let mut colors = Vec::with_capacity(positions.capacity());
+    for (particle, shade) in &particles {
+        positions.push(sim_settings.virt_to_world(&particle.0).translation.to_array());
+        colors.push(match shade {
+            Some(shade) if color_settings.mode != ColorMode::Uniform => {
+                let [r, g, b] = color_settings.colormap.sample(shade.0);
+                Color::srgb(r, g, b).to_linear().to_f32_array()
+            }
+            _ => uniform,
+        });
+    }
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
+
+//f switches between cubes and the point cloud
+fn render_mode_keybinds(actions: Actions, mut mode: ResMut<RenderMode>) {
+    if actions.just_pressed(Action::RenderMode) {
+        *mode = match *mode {
+            RenderMode::Cubes => RenderMode::Points,
+            RenderMode::Points => RenderMode::Cubes,
+        };
+    }
+}
+
+pub struct PointCloudPlugin;
+
+impl Plugin for PointCloudPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RenderMode>()
+            .add_systems(Startup, init_point_cloud)
+            .add_systems(Update, render_mode_keybinds)
+            .add_systems(Update, sync_render_mode.after(render_mode_keybinds))
+            .add_systems(PostUpdate, update_point_cloud);
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+
+use chaos_core::user_systems::{self, UserSystem, USER_SYSTEMS_FILE};
+
+use crate::keybindings::{Action, Actions};
+use crate::{ChaosEquationResource, LaunchOptions, SystemRegistry};
+
+/*
+    Typed Equations
+    e opens a prompt to type a system into, in the language from chaos_core::expression, one line with `;` between
+    the assignments: `dx = s*(y - x); dy = x*(r - z) - y; dz = x*y - b*z; s = 10; r = 28; b = 8/3`
+    enter adds it to the registry as typed_1, typed_2... and switches to it, escape closes the prompt
+    systems from a file (--equations, or equations.ron if it's there) get loaded on startup, see chaos_core::user_systems
+*/
+
+#[derive(Resource, Default)]
+pub struct EquationPrompt {
+    //what's been typed so far, while the prompt is open
+    pub text: Option<String>,
+    //what the file on startup or the last enter did, for the overlay
+    pub status: Option<String>,
+}
+
+fn load_user_systems(launch: Res<LaunchOptions>, mut registry: ResMut<SystemRegistry>, mut prompt: ResMut<EquationPrompt>) {
+    let path = match &launch.equations {
+        Some(path) => path.clone(),
+        None if std::path::Path::new(USER_SYSTEMS_FILE).exists() => USER_SYSTEMS_FILE.into(),
+        None => return,
+    };
+    prompt.status = Some(match user_systems::load(&path, &mut registry.0) {
+        Ok(count) => format!("loaded {count} equations from {}", path.display()),
+        Err(e) => {
+            error!("{e}");
+            e
+        }
+    });
+}
+
+fn open_prompt(actions: Actions, mut prompt: ResMut<EquationPrompt>) {
+    if actions.just_pressed(Action::TypeEquation) {
+        prompt.text = Some(String::new());
+        prompt.status = None;
+    }
+}
+
+//compiled and added, or why not
+fn submit(text: &str, registry: &mut SystemRegistry, eq: &mut ChaosEquationResource) -> Result<String, String> {
+    let n = (1..).find(|n| registry.0.find(&format!("typed_{n}")).is_none()).unwrap();
+    let user = UserSystem::typed(format!("typed_{n}"), format!("Typed {n}"), text.to_string());
+    let system = user.build()?;
+    registry.0.register(system);
+    eq.select(registry.0.len() - 1, &registry.0);
+    return Ok(format!("added typed_{n}"));
+}
+
+//while the prompt is open every key goes into it, and the rest of the app sees none of them
+fn type_into_prompt(
+    mut events: EventReader<KeyboardInput>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut prompt: ResMut<EquationPrompt>,
+    mut registry: ResMut<SystemRegistry>,
+    mut eq: ResMut<ChaosEquationResource>,
+) {
+    let Some(mut text) = prompt.text.take() else {
+        events.clear();
+        return;
+    };
+    let mut open = true;
+    for event in events.read().filter(|e| e.state == ButtonState::Pressed) {
+        match &event.logical_key {
+            Key::Character(c) => text.push_str(c),
+            Key::Space => text.push(' '),
+            Key::Backspace => {
+                text.pop();
+            }
+            Key::Escape => open = false,
+            Key::Enter => match submit(&text, &mut registry, &mut eq) {
+                Ok(status) => (open, prompt.status) = (false, Some(status)),
+                //stays open with the error under it, to fix it
+                Err(e) => prompt.status = Some(e),
+            },
+            _ => {}
+        }
+        if !open {
+            break;
+        }
+    }
+    events.clear();
+    if open {
+        prompt.text = Some(text);
+    }
+    keys.reset_all();
+}
+
+pub struct PromptPlugin;
+
+impl Plugin for PromptPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<EquationPrompt>()
+            .add_systems(Startup, load_user_systems)
+            .add_systems(PreUpdate, type_into_prompt.after(InputSystem))
+            .add_systems(Update, open_prompt);
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::utils::synccell::SyncCell;
+
+use crate::export::{self, Format, Row, RunInfo, TrajectoryWriter};
+use crate::keybindings::{Action, Actions};
+use crate::{integrators, simulation_running, vmove_particle_system, ChaosEquationResource, Particle, SystemRegistry};
+
+/*
+    Recording
+    while on, every particle's virtual coordinates get written out after every fixed step
+    files go to RECORDING_DIR, named after the system and when the recording started
+    the run info on top of a file has to hold for every row in it, so changing anything it lists ends the recording
+*/
+
+pub const RECORDING_DIR: &str = "recordings";
+
+//never reused, so rows from the same particle can be told apart across the whole run
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ParticleId(pub u64);
+
+#[derive(Resource, Default)]
+pub struct NextParticleId(pub u64);
+
+//system, parameters, integrator and dt - negative when running backwards
+type RunKey = (usize, Vec<f64>, usize, f64);
+
+fn run_key(eq: &ChaosEquationResource) -> RunKey {
+    return (eq.system, eq.params.clone(), eq.integrator, eq.step_dt());
+}
+
+//the first thing that differs between two keys, for the overlay
+fn what_changed(from: &RunKey, to: &RunKey) -> &'static str {
+    return if from.0 != to.0 {
+        "the system"
+    } else if from.1 != to.1 {
+        "a parameter"
+    } else if from.2 != to.2 {
+        "the integrator"
+    } else if from.3.abs() != to.3.abs() {
+        "dt"
+    } else {
+        "the direction of time"
+    };
+}
+
+#[derive(Resource)]
+pub struct Recording {
+    //what the next recording will be written as
+    pub format: Format,
+    //Some while recording
+    writer: Option<SyncCell<Box<dyn TrajectoryWriter + Send>>>,
+    pub path: Option<PathBuf>,
+    //what the run info in the file says - anything else ends the recording
+    key: Option<RunKey>,
+    //integration steps and simulated time since the recording started
+    pub step: u64,
+    pub t: f64,
+    pub rows: u64,
+    //why the last recording stopped, if it wasn't the user
+    pub error: Option<String>,
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        return Recording {
+            format: Format::Csv,
+            writer: None,
+            path: None,
+            key: None,
+            step: 0,
+            t: 0.0,
+            rows: 0,
+            error: None,
+        };
+    }
+}
+
+impl Recording {
+    pub fn is_recording(&self) -> bool {
+        return self.writer.is_some();
+    }
+
+    fn start(&mut self, key: RunKey, info: &RunInfo) {
+        self.stop();
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let path = PathBuf::from(RECORDING_DIR).join(format!("{}-{secs}.{}", info.system, self.format.extension()));
+        let writer = std::fs::create_dir_all(RECORDING_DIR)
+            .and_then(|_| export::create(&path, self.format, info));
+        match writer {
+            Ok(writer) => {
+                self.writer = Some(SyncCell::new(writer));
+                self.path = Some(path);
+                self.key = Some(key);
+                (self.step, self.t, self.rows) = (0, 0.0, 0);
+                self.error = None;
+            }
+            Err(e) => self.fail(format!("couldn't create {}: {e}", path.display())),
+        }
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut writer) = self.writer.take() {
+            if let Err(e) = writer.get().finish() {
+                self.fail(format!("couldn't finish {}: {e}", self.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default()));
+            }
+        }
+    }
+
+    fn fail(&mut self, message: String) {
+        error!("{message}");
+        self.writer = None;
+        self.error = Some(message);
+    }
+}
+
+//closing the app mid-recording still leaves a readable file
+impl Drop for Recording {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn assign_particle_ids(
+    mut cmd: Commands,
+    mut next: ResMut<NextParticleId>,
+    new_particles: Query<Entity, (With<Particle>, Without<ParticleId>)>,
+) {
+    for entity in &new_particles {
+        cmd.entity(entity).insert(ParticleId(next.0));
+        next.0 += 1;
+    }
+}
+
+fn record_particles(
+    mut recording: ResMut<Recording>,
+    eq: Res<ChaosEquationResource>,
+    particles: Query<(&ParticleId, &Particle)>,
+) {
+    //with no steps taken, the rows would be the last ones over again
+    if !recording.is_recording() || eq.steps == 0 {
+        return;
+    }
+    let key = run_key(&eq);
+    if let Some(changed) = recording.key.as_ref().filter(|k| **k != key).map(|k| what_changed(k, &key)) {
+        recording.stop();
+        //unless finishing the file went wrong, that's more important
+        recording.error.get_or_insert_with(|| format!("stopped when {changed} changed"));
+        return;
+    }
+    let recording = &mut *recording;
+    recording.step += eq.steps as u64;
+    recording.t += eq.step_dt() * eq.steps as f64;
+    let Some(writer) = recording.writer.as_mut() else {
+        return;
+    };
+    let writer = writer.get();
+    let mut result = Ok(());
+    for (id, particle) in &particles {
+        let row = Row { step: recording.step, t: recording.t, particle: id.0, at: particle.0 };
+        result = writer.write(&row);
+        if result.is_err() {
+            break;
+        }
+        recording.rows += 1;
+    }
+    if let Err(e) = result {
+        recording.fail(format!("recording stopped: {e}"));
+    }
+}
+
+//r starts/stops recording, shift+r picks the format
+fn recording_keybinds(
+    actions: Actions,
+    mut recording: ResMut<Recording>,
+    eq: Res<ChaosEquationResource>,
+    registry: Res<SystemRegistry>,
+) {
+    if actions.just_pressed(Action::RecordingFormat) {
+        if !recording.is_recording() {
+            recording.format = recording.format.next();
+        }
+        return;
+    }
+    if !actions.just_pressed(Action::ToggleRecording) {
+        return;
+    }
+    if recording.is_recording() {
+        recording.stop();
+    } else {
+        let system = registry.0.get(eq.system);
+        let info = RunInfo::new(system, &eq.params, eq.step_dt(), integrators::INTEGRATORS[eq.integrator]);
+        recording.start(run_key(&eq), &info);
+    }
+}
+
+pub struct RecordingPlugin;
+
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Recording>()
+            .init_resource::<NextParticleId>()
+            .add_systems(Update, assign_particle_ids)
+            .add_systems(Update, recording_keybinds)
+            .add_systems(FixedUpdate, record_particles.after(vmove_particle_system).run_if(simulation_running));
+    }
+}
@@ -0,0 +1,702 @@
+use std::sync::Arc;
+
+use crate::chaos_equations::{self as chaos, ChaosEq, Coord, LaneEq};
+use crate::expression::Equations;
+use crate::lanes::{Real, F64x4};
+
+//one tweakable parameter of a system, with the range it's allowed to take
+#[derive(Clone, Copy)]
+pub struct ParamDef {
+    pub name: &'static str,
+    pub default: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ParamDef {
+    pub const fn new(name: &'static str, default: f64, min: f64, max: f64) -> Self {
+        return ParamDef { name, default, min, max };
+    }
+
+    pub fn clamp(&self, value: f64) -> f64 {
+        return value.clamp(self.min, self.max);
+    }
+
+    //where the value sits in the allowed range, 0.0 to 1.0
+    pub fn fraction(&self, value: f64) -> f64 {
+        if self.max <= self.min {
+            return 0.0;
+        }
+        return ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0);
+    }
+}
+
+#[derive(Clone)]
+pub enum Equation {
+    //the plain and the four-lane version of the same equation, written in rust
+    Native { scalar: ChaosEq, lanes: LaneEq },
+    //typed in, see expression.rs
+    Typed(Arc<Equations>),
+}
+
+//both versions from one generic closure or function, so they can't drift apart
+macro_rules! equation {
+    ($f:expr) => {
+        Equation::Native { scalar: $f, lanes: $f }
+    };
+}
+
+//the fixed points for the given parameter values
+pub type EquilibriaFn = fn(&[f64]) -> Vec<Coord>;
+
+//a named system of equations; `eq` gets its parameter values in the same order as `params`
+#[derive(Clone)]
+pub struct ChaosSystem {
+    //stable identifier - what gets written to files and typed on the command line
+    pub id: &'static str,
+    //what gets shown on screen
+    pub name: &'static str,
+    pub params: &'static [ParamDef],
+    pub eq: Equation,
+    //a time step that keeps it stable without crawling
+    pub dt: f64,
+    //where to point the camera to see all of it
+    pub framing: Framing,
+    //the exact fixed points for the given parameters, for systems where they're known in closed form
+    //the others get theirs numerically, see fixed_points.rs
+    pub equilibria: Option<EquilibriaFn>,
+}
+
+//a view of the attractor in virtual coordinates - look at `center` from `radius` away
+#[derive(Clone, Copy)]
+pub struct Framing {
+    pub center: Coord,
+    pub radius: f64,
+}
+
+impl Framing {
+    pub const fn new(x: f64, y: f64, z: f64, radius: f64) -> Self {
+        return Framing { center: Coord { x, y, z }, radius };
+    }
+}
+
+impl ChaosSystem {
+    pub fn default_params(&self) -> Vec<f64> {
+        return self.params.iter().map(|p| p.default).collect();
+    }
+
+    //position of the parameter called `name` in `params`
+    pub fn param_index(&self, name: &str) -> Option<usize> {
+        return self.params.iter().position(|p| p.name == name);
+    }
+
+    pub fn derivative(&self, at: &Coord, params: &[f64]) -> Coord {
+        return match &self.eq {
+            Equation::Native { scalar, .. } => scalar(at, params),
+            Equation::Typed(equations) => equations.derivative(at, params),
+        };
+    }
+
+    pub fn derivative_x4(&self, at: &Coord<F64x4>, params: &[f64]) -> Coord<F64x4> {
+        return match &self.eq {
+            Equation::Native { lanes, .. } => lanes(at, params),
+            Equation::Typed(equations) => equations.derivative_x4(at, params),
+        };
+    }
+}
+
+//every system the app knows about - keybinds, the ui and saved files all go through here
+pub struct Registry {
+    systems: Vec<ChaosSystem>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        return Registry { systems: Vec::new() };
+    }
+
+    //all the systems that ship with the app
+    pub fn builtin() -> Self {
+        let mut registry = Registry::new();
+        registry.register(ChaosSystem {
+            id: "still",
+            name: "Still",
+            params: &[],
+            eq: equation!(chaos::basic_equation),
+            dt: 0.005,
+            framing: Framing::new(0.0, 0.0, 0.0, 40.0),
+            equilibria: Some(still_equilibria),
+        });
+        registry.register(ChaosSystem {
+            id: "lorenz",
+            name: "Lorenz",
+            params: LORENZ_PARAMS,
+            eq: equation!(|at, p| chaos::lorenz_attractor_general(at, p[0], p[1], p[2])),
+            dt: 0.005,
+            framing: Framing::new(0.0, 0.0, 25.0, 40.0),
+            equilibria: Some(lorenz_equilibria),
+        });
+        registry.register(ChaosSystem {
+            id: "rossler_variant1",
+            name: "Rössler (c=14)",
+            params: ROSSLER_PARAMS_VARIANT1,
+            eq: equation!(rossler),
+            dt: 0.01,
+            framing: Framing::new(0.0, 0.0, 10.0, 45.0),
+            equilibria: Some(rossler_equilibria),
+        });
+        registry.register(ChaosSystem {
+            id: "rossler_variant2",
+            name: "Rössler",
+            params: ROSSLER_PARAMS_VARIANT2,
+            eq: equation!(rossler),
+            dt: 0.01,
+            framing: Framing::new(0.0, 0.0, 5.0, 25.0),
+            equilibria: Some(rossler_equilibria),
+        });
+        registry.register(ChaosSystem {
+            id: "chen",
+            name: "Chen",
+            params: CHEN_PARAMS,
+            eq: equation!(|at, p| chaos::chen_attractor(at, p[0], p[1], p[2])),
+            dt: 0.002,
+            framing: Framing::new(0.0, 0.0, 28.0, 45.0),
+            equilibria: Some(chen_equilibria),
+        });
+        registry.register(ChaosSystem {
+            id: "lu",
+            name: "Lü",
+            params: LU_PARAMS,
+            eq: equation!(|at, p| chaos::lu_attractor(at, p[0], p[1], p[2])),
+            dt: 0.002,
+            framing: Framing::new(0.0, 0.0, 22.0, 40.0),
+            equilibria: Some(lu_equilibria),
+        });
+        registry.register(ChaosSystem {
+            id: "thomas",
+            name: "Thomas",
+            params: THOMAS_PARAMS,
+            eq: equation!(|at, p| chaos::thomas_attractor(at, p[0])),
+            dt: 0.05,
+            framing: Framing::new(0.0, 0.0, 0.0, 8.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "aizawa",
+            name: "Aizawa",
+            params: AIZAWA_PARAMS,
+            eq: equation!(|at, p| chaos::aizawa_attractor(at, p[0], p[1], p[2], p[3], p[4], p[5])),
+            dt: 0.01,
+            framing: Framing::new(0.0, 0.0, 0.5, 3.5),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "halvorsen",
+            name: "Halvorsen",
+            params: HALVORSEN_PARAMS,
+            eq: equation!(|at, p| chaos::halvorsen_attractor(at, p[0])),
+            dt: 0.005,
+            framing: Framing::new(-3.0, -3.0, -3.0, 18.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "dadras",
+            name: "Dadras",
+            params: DADRAS_PARAMS,
+            eq: equation!(|at, p| chaos::dadras_attractor(at, p[0], p[1], p[2], p[3], p[4])),
+            dt: 0.005,
+            framing: Framing::new(-2.0, -2.0, 1.0, 25.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "rabinovich_fabrikant",
+            name: "Rabinovich-Fabrikant",
+            params: RABINOVICH_FABRIKANT_PARAMS,
+            eq: equation!(|at, p| chaos::rabinovich_fabrikant_attractor(at, p[0], p[1])),
+            dt: 0.005,
+            framing: Framing::new(0.0, 0.0, 0.5, 5.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "four_wing",
+            name: "Four-Wing",
+            params: FOUR_WING_PARAMS,
+            eq: equation!(|at, p| chaos::four_wing_attractor(at, p[0], p[1], p[2])),
+            dt: 0.01,
+            framing: Framing::new(0.0, 0.0, 0.0, 5.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "chua",
+            name: "Chua's Circuit",
+            params: CHUA_PARAMS,
+            eq: equation!(|at, p| chaos::chua_circuit(at, p[0], p[1], p[2], p[3])),
+            dt: 0.01,
+            framing: Framing::new(0.0, 0.0, 0.0, 7.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "nose_hoover",
+            name: "Nosé-Hoover",
+            params: NOSE_HOOVER_PARAMS,
+            eq: equation!(|at, p| chaos::nose_hoover_attractor(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(-1.5, 0.0, 0.0, 6.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "lorenz84",
+            name: "Lorenz-84",
+            params: LORENZ84_PARAMS,
+            eq: equation!(|at, p| chaos::lorenz84_attractor(at, p[0], p[1], p[2], p[3])),
+            dt: 0.01,
+            framing: Framing::new(0.9, 0.0, 0.0, 5.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "burke_shaw",
+            name: "Burke-Shaw",
+            params: BURKE_SHAW_PARAMS,
+            eq: equation!(|at, p| chaos::burke_shaw_attractor(at, p[0], p[1])),
+            dt: 0.002,
+            framing: Framing::new(0.0, 0.0, 0.0, 5.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_a",
+            name: "Sprott A",
+            params: &[],
+            eq: equation!(|at, _| chaos::sprott_a(at)),
+            dt: 0.01,
+            framing: Framing::new(-1.5, 0.0, 0.0, 6.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_b",
+            name: "Sprott B",
+            params: &[],
+            eq: equation!(|at, _| chaos::sprott_b(at)),
+            dt: 0.01,
+            framing: Framing::new(0.0, 0.0, 0.0, 9.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_c",
+            name: "Sprott C",
+            params: &[],
+            eq: equation!(|at, _| chaos::sprott_c(at)),
+            dt: 0.01,
+            framing: Framing::new(-0.5, 0.0, -0.4, 9.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_d",
+            name: "Sprott D",
+            params: SPROTT_D_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_d(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(-2.2, 0.0, 2.3, 7.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_e",
+            name: "Sprott E",
+            params: SPROTT_E_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_e(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(0.4, 1.2, 1.5, 8.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_f",
+            name: "Sprott F",
+            params: SPROTT_F_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_f(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(-0.8, -1.7, 2.5, 7.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_g",
+            name: "Sprott G",
+            params: SPROTT_G_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_g(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(-0.7, -0.8, 0.0, 6.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_h",
+            name: "Sprott H",
+            params: SPROTT_H_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_h(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(-1.5, 1.9, -0.7, 8.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_i",
+            name: "Sprott I",
+            params: SPROTT_I_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_i(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(-0.2, -0.2, 0.1, 2.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_j",
+            name: "Sprott J",
+            params: SPROTT_J_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_j(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(10.0, -1.0, -4.0, 35.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_k",
+            name: "Sprott K",
+            params: SPROTT_K_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_k(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(-0.8, -0.3, 1.5, 6.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_l",
+            name: "Sprott L",
+            params: SPROTT_L_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_l(at, p[0], p[1])),
+            dt: 0.01,
+            framing: Framing::new(0.0, 22.0, -6.0, 30.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_m",
+            name: "Sprott M",
+            params: SPROTT_M_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_m(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(0.3, -3.0, -0.6, 8.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_n",
+            name: "Sprott N",
+            params: SPROTT_N_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_n(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(-10.0, -4.0, 0.0, 30.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_o",
+            name: "Sprott O",
+            params: SPROTT_O_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_o(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(-0.3, -0.1, -0.3, 2.5),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_p",
+            name: "Sprott P",
+            params: SPROTT_P_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_p(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(0.6, -0.25, 0.9, 3.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_q",
+            name: "Sprott Q",
+            params: SPROTT_Q_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_q(at, p[0], p[1])),
+            dt: 0.01,
+            framing: Framing::new(-1.0, -1.0, 2.0, 14.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_r",
+            name: "Sprott R",
+            params: SPROTT_R_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_r(at, p[0], p[1])),
+            dt: 0.01,
+            framing: Framing::new(-1.0, 2.0, -5.0, 18.0),
+            equilibria: None,
+        });
+        registry.register(ChaosSystem {
+            id: "sprott_s",
+            name: "Sprott S",
+            params: SPROTT_S_PARAMS,
+            eq: equation!(|at, p| chaos::sprott_s(at, p[0])),
+            dt: 0.01,
+            framing: Framing::new(-1.5, 0.6, 0.3, 6.0),
+            equilibria: None,
+        });
+        return registry;
+    }
+
+    //panics if the id is taken, since everything else relies on ids being unique
+    pub fn register(&mut self, system: ChaosSystem) {
+        assert!(self.find(system.id).is_none(), "system `{}` registered twice", system.id);
+        self.systems.push(system);
+    }
+
+    pub fn get(&self, index: usize) -> &ChaosSystem {
+        return &self.systems[index];
+    }
+
+    //index of the system with this id
+    pub fn find(&self, id: &str) -> Option<usize> {
+        return self.systems.iter().position(|s| s.id == id);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.systems.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.systems.is_empty();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ChaosSystem> {
+        return self.systems.iter();
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        return Registry::builtin();
+    }
+}
+
+/*
+    Parameter Sets
+*/
+
+const LORENZ_PARAMS: &[ParamDef] = &[
+    ParamDef::new("rho", 28.0, 0.0, 200.0),
+    ParamDef::new("sigma", 10.0, 0.0, 50.0),
+    ParamDef::new("beta", 8.0/3.0, 0.0, 10.0),
+];
+
+const ROSSLER_PARAMS_VARIANT1: &[ParamDef] = &[
+    ParamDef::new("a", 0.1, -0.5, 0.5),
+    ParamDef::new("b", 0.1, 0.0, 2.0),
+    ParamDef::new("c", 14.0, 0.0, 30.0),
+];
+
+const ROSSLER_PARAMS_VARIANT2: &[ParamDef] = &[
+    ParamDef::new("a", 0.2, -0.5, 0.5),
+    ParamDef::new("b", 0.2, 0.0, 2.0),
+    ParamDef::new("c", 5.7, 0.0, 30.0),
+];
+
+const CHEN_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 35.0, 0.0, 60.0),
+    ParamDef::new("b", 3.0, 0.0, 10.0),
+    ParamDef::new("c", 28.0, 0.0, 50.0),
+];
+
+const LU_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 36.0, 0.0, 60.0),
+    ParamDef::new("b", 3.0, 0.0, 10.0),
+    ParamDef::new("c", 20.0, 0.0, 40.0),
+];
+
+const THOMAS_PARAMS: &[ParamDef] = &[
+    ParamDef::new("b", 0.208186, 0.0, 1.0),
+];
+
+const AIZAWA_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 0.95, 0.0, 2.0),
+    ParamDef::new("b", 0.7, 0.0, 2.0),
+    ParamDef::new("c", 0.6, 0.0, 2.0),
+    ParamDef::new("d", 3.5, 0.0, 7.0),
+    ParamDef::new("e", 0.25, 0.0, 1.0),
+    ParamDef::new("f", 0.1, 0.0, 1.0),
+];
+
+const HALVORSEN_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 1.89, 0.0, 4.0),
+];
+
+const DADRAS_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 3.0, 0.0, 6.0),
+    ParamDef::new("b", 2.7, 0.0, 6.0),
+    ParamDef::new("c", 1.7, 0.0, 4.0),
+    ParamDef::new("d", 2.0, 0.0, 4.0),
+    ParamDef::new("e", 9.0, 0.0, 18.0),
+];
+
+const RABINOVICH_FABRIKANT_PARAMS: &[ParamDef] = &[
+    ParamDef::new("alpha", 0.14, 0.0, 1.5),
+    ParamDef::new("gamma", 0.1, 0.0, 1.0),
+];
+
+const FOUR_WING_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 0.2, -1.0, 1.0),
+    ParamDef::new("b", 0.01, -1.0, 1.0),
+    ParamDef::new("c", -0.4, -1.0, 1.0),
+];
+
+const CHUA_PARAMS: &[ParamDef] = &[
+    ParamDef::new("alpha", 15.6, 0.0, 30.0),
+    ParamDef::new("beta", 28.0, 0.0, 50.0),
+    ParamDef::new("m0", -1.143, -3.0, 0.0),
+    ParamDef::new("m1", -0.714, -3.0, 0.0),
+];
+
+const NOSE_HOOVER_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 1.0, 0.0, 5.0),
+];
+
+const LORENZ84_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 0.25, 0.0, 1.0),
+    ParamDef::new("b", 4.0, 0.0, 10.0),
+    ParamDef::new("F", 8.0, 0.0, 20.0),
+    ParamDef::new("G", 1.0, 0.0, 5.0),
+];
+
+const BURKE_SHAW_PARAMS: &[ParamDef] = &[
+    ParamDef::new("s", 10.0, 0.0, 20.0),
+    ParamDef::new("v", 4.272, 0.0, 10.0),
+];
+
+const SPROTT_D_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 3.0, 0.0, 6.0),
+];
+
+const SPROTT_E_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 4.0, 0.0, 8.0),
+];
+
+const SPROTT_F_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 0.5, 0.0, 1.0),
+];
+
+const SPROTT_G_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 0.4, 0.0, 1.0),
+];
+
+const SPROTT_H_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 0.5, 0.0, 1.0),
+];
+
+const SPROTT_I_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 0.2, 0.0, 1.0),
+];
+
+const SPROTT_J_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 2.0, 0.0, 4.0),
+];
+
+const SPROTT_K_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 0.3, 0.0, 1.0),
+];
+
+const SPROTT_L_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 3.9, 0.0, 8.0),
+    ParamDef::new("b", 0.9, 0.0, 2.0),
+];
+
+const SPROTT_M_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 1.7, 0.0, 4.0),
+];
+
+const SPROTT_N_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 2.0, 0.0, 4.0),
+];
+
+const SPROTT_O_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 2.7, 0.0, 6.0),
+];
+
+const SPROTT_P_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 2.7, 0.0, 6.0),
+];
+
+const SPROTT_Q_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 3.1, 0.0, 6.0),
+    ParamDef::new("b", 0.5, 0.0, 1.0),
+];
+
+const SPROTT_R_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 0.9, 0.0, 2.0),
+    ParamDef::new("b", 0.4, 0.0, 1.0),
+];
+
+const SPROTT_S_PARAMS: &[ParamDef] = &[
+    ParamDef::new("a", 4.0, 0.0, 8.0),
+];
+
+fn rossler<T: Real>(at: &Coord<T>, p: &[f64]) -> Coord<T> {
+    return chaos::rossler_attractor_general(at, p[0], p[1], p[2]);
+}
+
+/*
+    Equilibria
+    closed forms, for the systems that have them
+*/
+
+//everything stands still, so there's no point in marking any one spot
+fn still_equilibria(_: &[f64]) -> Vec<Coord> {
+    return Vec::new();
+}
+
+//the origin, and the centers of the two wings once rho > 1
+fn lorenz_equilibria(p: &[f64]) -> Vec<Coord> {
+    let (rho, beta) = (p[0], p[2]);
+    let mut points = vec![Coord::ZERO];
+    if beta * (rho - 1.0) > 0.0 {
+        let s = (beta * (rho - 1.0)).sqrt();
+        points.push(Coord { x: s, y: s, z: rho - 1.0 });
+        points.push(Coord { x: -s, y: -s, z: rho - 1.0 });
+    }
+    return points;
+}
+
+//x^2 - cx + ab = 0, y = -x/a, z = x/a
+fn rossler_equilibria(p: &[f64]) -> Vec<Coord> {
+    let (a, b, c) = (p[0], p[1], p[2]);
+    if a == 0.0 {
+        //then x = 0 and the quadratic is gone
+        return if c == 0.0 { Vec::new() } else { vec![Coord { x: 0.0, y: -b / c, z: b / c }] };
+    }
+    let discriminant = c * c - 4.0 * a * b;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    let root = discriminant.sqrt();
+    let mut points: Vec<Coord> = [(c - root) / 2.0, (c + root) / 2.0].iter()
+        .map(|&x| Coord { x, y: -x / a, z: x / a })
+        .collect();
+    if root == 0.0 {
+        points.pop();
+    }
+    return points;
+}
+
+//the origin, and (±sqrt(b(2c - a)), same, 2c - a)
+fn chen_equilibria(p: &[f64]) -> Vec<Coord> {
+    let (a, b, c) = (p[0], p[1], p[2]);
+    let mut points = vec![Coord::ZERO];
+    if b * (2.0 * c - a) > 0.0 {
+        let s = (b * (2.0 * c - a)).sqrt();
+        points.push(Coord { x: s, y: s, z: 2.0 * c - a });
+        points.push(Coord { x: -s, y: -s, z: 2.0 * c - a });
+    }
+    return points;
+}
+
+//the origin, and (±sqrt(bc), same, c)
+fn lu_equilibria(p: &[f64]) -> Vec<Coord> {
+    let (b, c) = (p[1], p[2]);
+    let mut points = vec![Coord::ZERO];
+    if b * c > 0.0 {
+        let s = (b * c).sqrt();
+        points.push(Coord { x: s, y: s, z: c });
+        points.push(Coord { x: -s, y: -s, z: c });
+    }
+    return points;
+}
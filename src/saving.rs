@@ -0,0 +1,212 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+
+use crate::keybindings::{Action, Actions};
+use crate::recording::{NextParticleId, ParticleId};
+use crate::settings;
+use crate::snapshot::{CameraSnapshot, ParticleSnapshot, RngSnapshot, Snapshot, SNAPSHOT_VERSION};
+use crate::{
+    chaos, integrators, point_cloud, spawn_particle, ChaosEquationResource, CubeMeshMaterial, FramedSystem,
+    LaunchOptions, PanOrbitState, ParamSelection, Particle, ParticleCount, SimRng, SimSettings, SystemRegistry,
+};
+
+/*
+    Saving and Loading
+    f5 writes the whole simulation to SNAPSHOT_DIR, f9 loads the newest file there back
+    a specific file can be loaded on startup with `--load <file>`
+*/
+
+pub const SNAPSHOT_DIR: &str = "snapshots";
+
+//what the last save or load did, for the overlay
+#[derive(Resource, Default)]
+pub struct SnapshotStatus(pub Option<String>);
+
+fn take_snapshot(
+    eq: &ChaosEquationResource,
+    registry: &SystemRegistry,
+    camera: &PanOrbitState,
+    rng: &SimRng,
+    particles: &Query<(&Particle, Option<&ParticleId>)>,
+    sim_settings: &SimSettings,
+) -> Snapshot {
+    let system = registry.0.get(eq.system);
+    let mut saved: Vec<ParticleSnapshot> = particles.iter()
+        //a particle spawned this very frame doesn't have an id yet, it'll make the next one
+        .filter_map(|(p, id)| Some(ParticleSnapshot { id: id?.0, at: [p.0.x, p.0.y, p.0.z] }))
+        .collect();
+    saved.sort_by_key(|p| p.id);
+    let zoom = sim_settings.virt_zoom as f32;
+    return Snapshot {
+        version: SNAPSHOT_VERSION,
+        system: system.id.to_string(),
+        params: system.params.iter().zip(&eq.params).map(|(def, v)| (def.name.to_string(), *v)).collect(),
+        integrator: integrators::INTEGRATORS[eq.integrator].name().to_string(),
+        steps: eq.steps,
+        sim_dt: Some(eq.sim_dt),
+        dt_mult: eq.dt_mult,
+        camera: CameraSnapshot {
+            center: (camera.center / zoom).to_array(),
+            radius: camera.radius / zoom,
+            pitch: camera.pitch,
+            yaw: camera.yaw,
+            upside_down: camera.upside_down,
+        },
+        particles: saved,
+        rng: Some(RngSnapshot { seed: rng.seed, word_pos: rng.rng.get_word_pos() as u64 }),
+    };
+}
+
+//everything a snapshot touches when it gets loaded
+#[derive(bevy::ecs::system::SystemParam)]
+struct Restore<'w, 's> {
+    cmd: Commands<'w, 's>,
+    eq: ResMut<'w, ChaosEquationResource>,
+    registry: Res<'w, SystemRegistry>,
+    framed: ResMut<'w, FramedSystem>,
+    selection: ResMut<'w, ParamSelection>,
+    pc: ResMut<'w, ParticleCount>,
+    next_id: ResMut<'w, NextParticleId>,
+    rng: ResMut<'w, SimRng>,
+    render_mode: Res<'w, point_cloud::RenderMode>,
+    asset: Res<'w, CubeMeshMaterial>,
+    sim_settings: Res<'w, SimSettings>,
+    q_camera: Query<'w, 's, (&'static mut PanOrbitState, &'static mut Transform)>,
+    existing: Query<'w, 's, Entity, With<Particle>>,
+}
+
+impl Restore<'_, '_> {
+    fn apply(&mut self, snapshot: &Snapshot) -> Result<(), String> {
+        let system_index = self.registry.0.find(&snapshot.system)
+            .ok_or(format!("unknown system `{}`", snapshot.system))?;
+        let system = self.registry.0.get(system_index);
+        let integrator = integrators::INTEGRATORS.iter().position(|i| i.name() == snapshot.integrator)
+            .ok_or(format!("unknown integrator `{}`", snapshot.integrator))?;
+
+        //parameters missing from the file keep their defaults, ones the system doesn't have are ignored
+        self.eq.select(system_index, &self.registry.0);
+        for (def, value) in system.params.iter().zip(self.eq.params.iter_mut()) {
+            if let Some(saved) = snapshot.params.get(def.name) {
+                *value = *saved;
+            }
+        }
+        self.eq.integrator = integrator;
+        self.eq.steps = snapshot.steps;
+        self.eq.dt_mult = snapshot.dt_mult;
+        //files from before it was saved run at whatever dt the settings give them
+        if let Some(sim_dt) = snapshot.sim_dt {
+            self.eq.sim_dt = sim_dt;
+        }
+        //otherwise the switch would get framed like a fresh pick, overriding dt and the camera
+        self.framed.0 = Some(system_index);
+        self.selection.0 = 0;
+
+        let zoom = snapshot.camera_scale(self.sim_settings.virt_zoom);
+        for (mut state, mut transform) in &mut self.q_camera {
+            let c = &snapshot.camera;
+            state.center = Vec3::from_array(c.center) * zoom;
+            state.radius = c.radius * zoom;
+            state.pitch = c.pitch;
+            state.yaw = c.yaw;
+            state.upside_down = c.upside_down;
+            transform.rotation = Quat::from_euler(EulerRot::YXZ, state.yaw, state.pitch, 0.0);
+            transform.translation = state.center + transform.back() * state.radius;
+        }
+
+        for entity in &self.existing {
+            self.cmd.entity(entity).despawn();
+        }
+        for p in &snapshot.particles {
+            let at = chaos::Coord { x: p.at[0], y: p.at[1], z: p.at[2] };
+            spawn_particle(&mut self.cmd, at, *self.render_mode, &self.asset, &self.sim_settings).insert(ParticleId(p.id));
+        }
+        self.pc.0 = snapshot.particles.len() as i32;
+        let after_last = snapshot.particles.iter().map(|p| p.id + 1).max().unwrap_or(0);
+        self.next_id.0 = self.next_id.0.max(after_last);
+        //files from before the rng was saved keep whatever stream is running now
+        if let Some(saved) = snapshot.rng {
+            *self.rng = SimRng::new(saved.seed);
+            self.rng.rng.set_word_pos(saved.word_pos as u128);
+        }
+        return Ok(());
+    }
+
+    fn load(&mut self, path: &Path) -> String {
+        let result = Snapshot::load(path)
+            .map_err(|e| e.to_string())
+            .and_then(|snapshot| self.apply(&snapshot));
+        return match result {
+            Ok(()) => format!("loaded {}", path.display()),
+            Err(e) => {
+                error!("couldn't load {}: {e}", path.display());
+                format!("couldn't load {}: {e}", path.display())
+            }
+        };
+    }
+}
+
+//the most recently written snapshot
+fn newest_snapshot() -> Option<PathBuf> {
+    return std::fs::read_dir(SNAPSHOT_DIR).ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "ron"))
+        .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok())
+        .map(|entry| entry.path());
+}
+
+fn save(snapshot: &Snapshot) -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let path = PathBuf::from(SNAPSHOT_DIR).join(format!("{}-{secs}.ron", snapshot.system));
+    return match std::fs::create_dir_all(SNAPSHOT_DIR).and_then(|_| snapshot.save(&path)) {
+        Ok(()) => format!("saved {}", path.display()),
+        Err(e) => {
+            error!("couldn't save {}: {e}", path.display());
+            format!("couldn't save {}: {e}", path.display())
+        }
+    };
+}
+
+//f5 saves, f9 loads the newest save
+fn snapshot_keybinds(
+    actions: Actions,
+    mut status: ResMut<SnapshotStatus>,
+    particles: Query<(&Particle, Option<&ParticleId>)>,
+    mut restore: Restore,
+) {
+    if actions.just_pressed(Action::SaveSnapshot) {
+        if let Ok((camera, _)) = restore.q_camera.get_single() {
+            let snapshot = take_snapshot(&restore.eq, &restore.registry, camera, &restore.rng, &particles, &restore.sim_settings);
+            status.0 = Some(save(&snapshot));
+        }
+    }
+    if actions.just_pressed(Action::LoadSnapshot) {
+        status.0 = Some(match newest_snapshot() {
+            Some(path) => restore.load(&path),
+            None => format!("nothing saved in {SNAPSHOT_DIR}/ yet"),
+        });
+    }
+}
+
+//the file given with --load, once the settings are in - so their dt and zoom don't get applied over the snapshot's
+fn load_on_startup(launch: Res<LaunchOptions>, mut done: Local<bool>, mut status: ResMut<SnapshotStatus>, mut restore: Restore) {
+    if *done {
+        return;
+    }
+    *done = true;
+    if let Some(path) = &launch.load {
+        status.0 = Some(restore.load(path));
+    }
+}
+
+pub struct SavingPlugin;
+
+impl Plugin for SavingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<SnapshotStatus>()
+            .add_systems(Update, load_on_startup.after(settings::apply_settings).run_if(settings::settings_loaded))
+            .add_systems(Update, snapshot_keybinds);
+    }
+}
@@ -0,0 +1,291 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::color::palettes::css::{GRAY, LIGHT_SKY_BLUE};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use chaos_core::ensemble::Ensemble;
+use chaos_core::export::{self, Row, RunInfo, TrajectoryWriter};
+use chaos_core::poincare::{Axis, Direction, Plane};
+
+use crate::keybindings::{Action, Actions};
+use crate::recording::{Recording, RECORDING_DIR};
+use crate::sensitivity::{PlotGizmos, PLOT_MARGIN, PLOT_SIZE};
+use crate::settings::SimSettings;
+use crate::{integrators, ChaosEquationResource, SystemRegistry};
+
+/*
+    Poincaré Section
+    o turns it on: every particle going through a plane gets its crossing point kept (see chaos_core::poincare),
+    drawn flat in the bottom left corner, and p writes them all out in the recording format
+    the crossings are found in vmove_particle_system, which steps one step at a time while this is on
+*/
+
+//beyond this many crossings new ones are dropped
+pub const SECTION_POINTS_MAX: usize = 200_000;
+//only the latest ones are drawn, gizmos aren't made for more
+pub const SECTION_POINTS_DRAWN: usize = 20_000;
+//page up/down move the plane by this fraction of the attractor's size
+pub const SECTION_NUDGE: f64 = 0.02;
+
+#[derive(Resource)]
+pub struct Section {
+    pub enabled: bool,
+    pub plane: Plane,
+    //every crossing so far, with `at` on the plane and `t` interpolated between the steps either side of it
+    pub points: Vec<Row>,
+    //steps and simulated time since the points were last cleared - both go down while time runs backwards
+    step: i64,
+    t: f64,
+    //the furthest step taken, crossings up to it are in already
+    reached: i64,
+    //the system and parameters the points are for
+    key: Option<(usize, Vec<f64>)>,
+    //what the last export did, for the overlay
+    pub status: Option<String>,
+}
+
+impl Default for Section {
+    fn default() -> Self {
+        return Section {
+            enabled: false,
+            plane: Plane { axis: Axis::Z, value: 0.0, direction: Direction::Rising },
+            points: Vec::new(),
+            step: 0,
+            t: 0.0,
+            reached: 0,
+            key: None,
+            status: None,
+        };
+    }
+}
+
+impl Section {
+    pub fn clear(&mut self) {
+        self.points.clear();
+        (self.step, self.t, self.reached) = (0, 0.0, 0);
+    }
+
+    //one step went from `before` to `after`; `ids` has every particle's ParticleId, in the same order
+    //a negative dt is a step back in time - nothing new gets crossed, and neither does anything on the way forward again
+    //until it's past where it went back from (and backwards, rising crossings would look like falling ones anyway)
+    pub fn record(&mut self, before: &Ensemble, after: &Ensemble, ids: &[Option<u64>], dt: f64) {
+        if dt < 0.0 {
+            self.step -= 1;
+            self.t += dt;
+            return;
+        }
+        if self.step >= self.reached {
+            for (i, f, at) in self.plane.crossings(before, after) {
+                //a particle spawned this very frame doesn't have an id yet, so it can't be told apart in the file
+                let (Some(particle), true) = (ids[i], self.points.len() < SECTION_POINTS_MAX) else {
+                    continue;
+                };
+                self.points.push(Row { step: (self.step + 1) as u64, t: self.t + f * dt, particle, at });
+            }
+        }
+        self.step += 1;
+        self.t += dt;
+        self.reached = self.reached.max(self.step);
+    }
+
+    //through the middle of the attractor
+    fn center_plane(&mut self, framing: &chaos_core::registry::Framing) {
+        self.plane.value = self.plane.axis.of(&framing.center);
+    }
+}
+
+//start over when the system or its parameters change, the old crossings belong to a different map
+fn follow_system(mut section: ResMut<Section>, eq: Res<ChaosEquationResource>, registry: Res<SystemRegistry>) {
+    let key = (eq.system, eq.params.clone());
+    if section.key.as_ref() == Some(&key) {
+        return;
+    }
+    if section.key.as_ref().map(|k| k.0) != Some(eq.system) {
+        section.center_plane(&registry.0.get(eq.system).framing);
+    }
+    section.clear();
+    section.key = Some(key);
+}
+
+fn export(section: &Section, eq: &ChaosEquationResource, registry: &SystemRegistry, format: export::Format) -> String {
+    let system = registry.0.get(eq.system);
+    let mut info = RunInfo::new(system, &eq.params, eq.dt(), integrators::INTEGRATORS[eq.integrator]);
+    info.section = Some(section.plane.to_string());
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let path = PathBuf::from(RECORDING_DIR).join(format!("{}-section-{secs}.{}", system.id, format.extension()));
+    let write = |writer: &mut dyn TrajectoryWriter| {
+        for row in &section.points {
+            writer.write(row)?;
+        }
+        return writer.finish();
+    };
+    let result = std::fs::create_dir_all(RECORDING_DIR)
+        .and_then(|_| export::create(&path, format, &info))
+        .and_then(|mut writer| write(&mut *writer));
+    return match result {
+        Ok(()) => format!("wrote {} crossings to {}", section.points.len(), path.display()),
+        Err(e) => {
+            error!("couldn't write {}: {e}", path.display());
+            format!("couldn't write {}: {e}", path.display())
+        }
+    };
+}
+
+//o toggles, shift+o picks the axis, ctrl+o the direction, page up/down move the plane, p exports
+fn section_keybinds(
+    actions: Actions,
+    mut section: ResMut<Section>,
+    eq: Res<ChaosEquationResource>,
+    registry: Res<SystemRegistry>,
+    recording: Res<Recording>,
+) {
+    let framing = &registry.0.get(eq.system).framing;
+    if actions.just_pressed(Action::SectionAxis) {
+        section.plane.axis = section.plane.axis.next();
+        section.center_plane(framing);
+        section.clear();
+    } else if actions.just_pressed(Action::SectionDirection) {
+        section.plane.direction = section.plane.direction.next();
+        section.clear();
+    } else if actions.just_pressed(Action::ToggleSection) {
+        section.enabled = !section.enabled;
+        section.clear();
+    }
+    let nudge = if actions.just_pressed(Action::SectionUp) {
+        1.0
+    } else if actions.just_pressed(Action::SectionDown) {
+        -1.0
+    } else {
+        0.0
+    };
+    if nudge != 0.0 {
+        //rounded, so the overlay and the files say 26.6 and not 26.599999999999998
+        let value = section.plane.value + nudge * framing.radius * SECTION_NUDGE;
+        section.plane.value = (value * 1e6).round() / 1e6;
+        section.clear();
+    }
+    if actions.just_pressed(Action::ExportSection) {
+        section.status = Some(export(&section, &eq, &registry, recording.format));
+    }
+}
+
+//the plane itself in the 3d view, as a square as big as the attractor
+fn draw_plane(
+    mut gizmos: Gizmos,
+    section: Res<Section>,
+    eq: Res<ChaosEquationResource>,
+    registry: Res<SystemRegistry>,
+    sim_settings: Res<SimSettings>,
+) {
+    if !section.enabled {
+        return;
+    }
+    let framing = &registry.0.get(eq.system).framing;
+    let r = framing.radius;
+    let corner = |u: f64, v: f64| {
+        let mut c = framing.center;
+        let (du, dv) = (u * r, v * r);
+        match section.plane.axis {
+            Axis::X => (c.x, c.y, c.z) = (section.plane.value, c.y + du, c.z + dv),
+            Axis::Y => (c.x, c.y, c.z) = (c.x + du, section.plane.value, c.z + dv),
+            Axis::Z => (c.x, c.y, c.z) = (c.x + du, c.y + dv, section.plane.value),
+        }
+        return sim_settings.virt_to_world(&c).translation;
+    };
+    gizmos.linestrip(
+        [corner(-1.0, -1.0), corner(1.0, -1.0), corner(1.0, 1.0), corner(-1.0, 1.0), corner(-1.0, -1.0)],
+        GRAY,
+    );
+}
+
+//the crossings, flat, in the bottom left corner
+fn draw_section(mut gizmos: Gizmos<PlotGizmos>, section: Res<Section>, q_window: Query<&Window, With<PrimaryWindow>>) {
+    let Ok(window) = q_window.get_single() else {
+        return;
+    };
+    if !section.enabled {
+        return;
+    }
+    let corner = Vec2::new(-window.width() / 2.0 + PLOT_MARGIN, -window.height() / 2.0 + PLOT_MARGIN);
+    gizmos.rect_2d(corner + PLOT_SIZE / 2.0, 0.0, PLOT_SIZE, GRAY);
+
+    let shown = &section.points[section.points.len().saturating_sub(SECTION_POINTS_DRAWN)..];
+    let projected: Vec<(f64, f64)> = shown.iter().map(|row| section.plane.project(&row.at)).collect();
+    let (mut lo, mut hi) = ((f64::INFINITY, f64::INFINITY), (f64::NEG_INFINITY, f64::NEG_INFINITY));
+    for &(u, v) in &projected {
+        (lo.0, lo.1) = (lo.0.min(u), lo.1.min(v));
+        (hi.0, hi.1) = (hi.0.max(u), hi.1.max(v));
+    }
+    let span = ((hi.0 - lo.0).max(f64::EPSILON), (hi.1 - lo.1).max(f64::EPSILON));
+    for (u, v) in projected {
+        let at = corner + PLOT_SIZE * Vec2::new(((u - lo.0) / span.0) as f32, ((v - lo.1) / span.1) as f32);
+        //a dot is a line one pixel long
+        gizmos.line_2d(at, at + Vec2::X, LIGHT_SKY_BLUE);
+    }
+}
+
+pub struct SectionPlugin;
+
+impl Plugin for SectionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Section>()
+            .add_systems(Update, follow_system)
+            .add_systems(Update, section_keybinds.after(follow_system))
+            .add_systems(Update, draw_plane)
+            .add_systems(Update, draw_section);
+    }
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chaos_core::registry::Registry;
+
+    //lorenz through z = 27 both ways, with a handful of particles and every step handed to the section like vmove does
+    #[test]
+    fn retracing_records_nothing_twice() {
+        let registry = Registry::builtin();
+        let lorenz = registry.get(registry.find("lorenz").unwrap());
+        let params = lorenz.default_params();
+        let integrator = integrators::find("rk4").unwrap();
+        let mut section = Section {
+            plane: Plane { axis: Axis::Z, value: 27.0, direction: Direction::Both },
+            ..default()
+        };
+        let mut ensemble: Ensemble = (0..4).map(|i| chaos_core::chaos_equations::Coord { x: 1.0 + i as f64, y: 1.0, z: 20.0 }).collect();
+        let ids: Vec<Option<u64>> = (0..4).map(Some).collect();
+        let run = |section: &mut Section, ensemble: &mut Ensemble, dt: f64, steps: usize| {
+            for _ in 0..steps {
+                let before = ensemble.clone();
+                ensemble.step(lorenz, &params, integrator, dt, 1);
+                section.record(&before, ensemble, &ids, dt);
+            }
+        };
+
+        run(&mut section, &mut ensemble, 0.005, 2000);
+        let first = section.points.len();
+        assert!(first > 20, "{first}");
+        //not for long, lorenz flies apart backwards
+        run(&mut section, &mut ensemble, -0.005, 100);
+        assert_eq!(section.points.len(), first);
+        run(&mut section, &mut ensemble, 0.005, 100);
+        assert_eq!(section.points.len(), first);
+        //only past the furthest point do new ones come in, and they carry on from where the steps and time left off
+        run(&mut section, &mut ensemble, 0.005, 1000);
+        assert!(section.points.len() > first);
+        let mut seen: Vec<(u64, u64)> = section.points.iter().map(|row| (row.particle, row.step)).collect();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), section.points.len());
+        for row in &section.points {
+            assert!((row.t - row.step as f64 * 0.005).abs() < 0.005 + 1e-9, "step {} at t {}", row.step, row.t);
+        }
+    }
+}
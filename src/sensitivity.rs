@@ -0,0 +1,193 @@
+use bevy::color::palettes::css::{GRAY, ORANGE, WHITE_SMOKE};
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::window::PrimaryWindow;
+
+use chaos_core::lyapunov::{self, Lyapunov};
+use chaos_core::spawn::{Pattern, Shape};
+
+use crate::chaos_equations as chaos;
+use crate::keybindings::{Action, Actions};
+use crate::{
+    integrators, simulation_running, vmove_particle_system, ChaosEquationResource, Particle, SimRng, Spawner, SystemRegistry,
+};
+
+/*
+    Sensitivity
+    a lyapunov estimate for whatever is being simulated, kept up to date in the background for the overlay
+    it follows its own point, not any particle, and starts over whenever the system, a parameter, the integrator or dt changes
+
+    v also drops a pair of twin particles PAIR_SEPARATION apart onto the attractor and plots ln(their distance) over time
+    in a corner - a straight climb while they separate exponentially, with the fitted slope, then a flat line once they're
+    as far apart as the attractor lets them be
+*/
+
+//estimator steps per FixedUpdate, independent of how fast the particles go
+pub const LYAPUNOV_STEPS_PER_TICK: usize = 64;
+//simulated time to settle onto the attractor before measuring
+pub const LYAPUNOV_TRANSIENT: f64 = 5.0;
+
+#[derive(Resource, Default)]
+pub struct LiveLyapunov {
+    pub estimate: Option<Lyapunov>,
+    //still settling, the exponents so far don't mean anything yet
+    pub settling: bool,
+    //what the estimate was started for
+    key: Option<(usize, Vec<f64>, usize, f64)>,
+}
+
+fn step_lyapunov(mut live: ResMut<LiveLyapunov>, eq: Res<ChaosEquationResource>, registry: Res<SystemRegistry>) {
+    let system = registry.0.get(eq.system);
+    let key = (eq.system, eq.params.clone(), eq.integrator, eq.dt());
+    if live.key.as_ref() != Some(&key) {
+        //just off the middle of the view, which is often a fixed point
+        let at = system.framing.center + chaos::Coord { x: 0.1, y: 0.1, z: 0.1 };
+        live.estimate = Some(Lyapunov::new(at));
+        live.settling = true;
+        live.key = Some(key);
+    }
+    let settling = live.settling;
+    let Some(estimate) = live.estimate.as_mut() else {
+        return;
+    };
+    if !estimate.is_finite() {
+        return;
+    }
+    let integrator = integrators::INTEGRATORS[eq.integrator];
+    for _ in 0..LYAPUNOV_STEPS_PER_TICK {
+        estimate.step(system, &eq.params, integrator, eq.dt());
+    }
+    if settling && estimate.time >= LYAPUNOV_TRANSIENT {
+        estimate.restart();
+        live.settling = false;
+    }
+}
+
+//a plot this long is about a minute at 64 ticks a second, long past saturation
+pub const DIVERGENCE_SAMPLES_MAX: usize = 4096;
+//twins further apart than this fraction of the attractor's size are saturated, and left out of the fit
+pub const DIVERGENCE_SATURATION: f64 = 0.05;
+//in pixels, the plot sits in the bottom right corner
+pub const PLOT_SIZE: Vec2 = Vec2::new(360.0, 200.0);
+pub const PLOT_MARGIN: f32 = 16.0;
+//the plot is drawn by its own 2d camera on top of the 3d one, which only sees this layer
+pub const PLOT_LAYER: usize = 1;
+
+#[derive(Resource, Default)]
+pub struct Divergence {
+    pub twins: Option<(Entity, Entity)>,
+    //(simulated time, ln distance) after every FixedUpdate
+    pub samples: Vec<(f64, f64)>,
+    //the first this many samples haven't saturated, the fit covers only those
+    pub fitted: usize,
+    //slope and intercept of the fit
+    pub fit: Option<(f64, f64)>,
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct PlotGizmos;
+
+fn spawn_plot_camera(mut cmd: Commands, mut config_store: ResMut<GizmoConfigStore>) {
+    cmd.spawn((
+        Camera2dBundle {
+            camera: Camera { order: 1, clear_color: ClearColorConfig::None, ..default() },
+            ..default()
+        },
+        RenderLayers::layer(PLOT_LAYER),
+    ));
+    let (config, _) = config_store.config_mut::<PlotGizmos>();
+    config.render_layers = RenderLayers::layer(PLOT_LAYER);
+}
+
+//v starts tracking a new pair of twins, or stops tracking them
+fn divergence_keybinds(
+    actions: Actions,
+    mut divergence: ResMut<Divergence>,
+    mut spawner: Spawner,
+    live: Res<LiveLyapunov>,
+    eq: Res<ChaosEquationResource>,
+    registry: Res<SystemRegistry>,
+    mut rng: ResMut<SimRng>,
+) {
+    if !actions.just_pressed(Action::TwinParticles) {
+        return;
+    }
+    if divergence.twins.is_some() {
+        *divergence = Divergence::default();
+        return;
+    }
+    //the lyapunov estimate's point is already on the attractor, so the plot doesn't start with the fall onto it
+    let at = match &live.estimate {
+        Some(l) if !live.settling && l.is_finite() => l.at,
+        _ => registry.0.get(eq.system).framing.center + chaos::Coord { x: 0.1, y: 0.1, z: 0.1 },
+    };
+    let pair = Shape { pattern: Pattern::Pair, ..default() }.generate(&mut rng.rng, at);
+    let twins = (spawner.spawn(pair[0]), spawner.spawn(pair[1]));
+    *divergence = Divergence { twins: Some(twins), ..default() };
+}
+
+fn track_divergence(
+    mut divergence: ResMut<Divergence>,
+    particles: Query<&Particle>,
+    eq: Res<ChaosEquationResource>,
+    registry: Res<SystemRegistry>,
+) {
+    let Some((a, b)) = divergence.twins else {
+        return;
+    };
+    //cleared away, keep the plot as it is
+    let (Ok(a), Ok(b)) = (particles.get(a), particles.get(b)) else {
+        return;
+    };
+    if divergence.samples.len() >= DIVERGENCE_SAMPLES_MAX {
+        return;
+    }
+    let d = a.0 - b.0;
+    let distance = (d.x * d.x + d.y * d.y + d.z * d.z).sqrt();
+    //time gone by, not simulated time - running backwards keeps the plot going right, with the twins closing in
+    let t = divergence.samples.last().map_or(0.0, |s| s.0) + eq.dt() * eq.steps as f64;
+    divergence.samples.push((t, distance.ln()));
+
+    let saturated = distance > registry.0.get(eq.system).framing.radius * DIVERGENCE_SATURATION;
+    if !saturated && divergence.fitted == divergence.samples.len() - 1 {
+        divergence.fitted += 1;
+        divergence.fit = lyapunov::fit_line(&divergence.samples[..divergence.fitted]);
+    }
+}
+
+fn draw_divergence(mut gizmos: Gizmos<PlotGizmos>, divergence: Res<Divergence>, q_window: Query<&Window, With<PrimaryWindow>>) {
+    let samples = &divergence.samples;
+    let (Ok(window), Some(last)) = (q_window.get_single(), samples.last()) else {
+        return;
+    };
+    //the 2d camera has the origin in the middle of the window, y up
+    let corner = Vec2::new(window.width() / 2.0 - PLOT_MARGIN - PLOT_SIZE.x, -window.height() / 2.0 + PLOT_MARGIN);
+    let lo = samples.iter().map(|s| s.1).fold(f64::INFINITY, f64::min);
+    let hi = samples.iter().map(|s| s.1).fold(f64::NEG_INFINITY, f64::max);
+    let t_max = last.0.max(f64::EPSILON);
+    let y_range = (hi - lo).max(1.0);
+    let to_screen = |t: f64, y: f64| corner + PLOT_SIZE * Vec2::new((t / t_max) as f32, ((y - lo) / y_range) as f32);
+
+    gizmos.rect_2d(corner + PLOT_SIZE / 2.0, 0.0, PLOT_SIZE, GRAY);
+    gizmos.linestrip_2d(samples.iter().map(|s| to_screen(s.0, s.1)), WHITE_SMOKE);
+    if let Some((slope, intercept)) = divergence.fit {
+        let t_end = samples[divergence.fitted - 1].0;
+        gizmos.line_2d(to_screen(0.0, intercept), to_screen(t_end, intercept + slope * t_end), ORANGE);
+    }
+}
+
+pub struct SensitivityPlugin;
+
+impl Plugin for SensitivityPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<LiveLyapunov>()
+            .init_resource::<Divergence>()
+            .init_gizmo_group::<PlotGizmos>()
+            .add_systems(Startup, spawn_plot_camera)
+            .add_systems(FixedUpdate, step_lyapunov)
+            .add_systems(FixedUpdate, track_divergence.after(vmove_particle_system).run_if(simulation_running))
+            .add_systems(Update, divergence_keybinds)
+            .add_systems(Update, draw_divergence);
+    }
+}
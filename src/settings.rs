@@ -0,0 +1,202 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::chaos_equations as chaos;
+use crate::{
+    ChaosEquationResource, CubeMeshMaterial, PanOrbitState, CAMERA_FOV, GIZMOS_AXES_LENGTH, LIGHT_STRENGTH, SIM_DT,
+    SIZE_PARTICLE, VIRT_ZOOM,
+};
+
+/*
+    Settings
+    the scale and looks of the scene, from assets/settings.ron - it goes through the asset server, which watches the file,
+    so saving it while the app runs applies the new values right away
+    whatever the file leaves out (or all of it, if there's no file) stays at the defaults, the consts in main.rs
+*/
+
+//relative to the assets folder
+pub const SETTINGS_FILE: &str = "settings.ron";
+
+#[derive(Asset, Resource, TypePath, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct SimSettings {
+    //world units per virtual unit
+    pub virt_zoom: f64,
+    //the time step before [ and ] scale it
+    pub sim_dt: f64,
+    //edge of a particle's cube, in world units
+    pub size_particle: f32,
+    //vertical, in radians
+    pub camera_fov: f32,
+    //ambient light
+    pub light_strength: f32,
+    pub gizmos_axes_length: f32,
+}
+
+impl Default for SimSettings {
+    fn default() -> Self {
+        return SimSettings {
+            virt_zoom: VIRT_ZOOM,
+            sim_dt: SIM_DT,
+            size_particle: SIZE_PARTICLE,
+            camera_fov: CAMERA_FOV,
+            light_strength: LIGHT_STRENGTH,
+            gizmos_axes_length: GIZMOS_AXES_LENGTH,
+        };
+    }
+}
+
+impl SimSettings {
+    pub fn world_to_virt(&self, at: Vec3) -> chaos::Coord {
+        return chaos::Coord {
+            x: at.x as f64 / self.virt_zoom,
+            y: at.y as f64 / self.virt_zoom,
+            z: at.z as f64 / self.virt_zoom,
+        };
+    }
+
+    pub fn virt_to_world(&self, c: &chaos::Coord) -> Transform {
+        let z = self.virt_zoom;
+        return Transform::from_xyz((c.x * z) as f32, (c.y * z) as f32, (c.z * z) as f32);
+    }
+
+    //anything that would turn every particle into NaN, or the camera inside out
+    fn validate(&self) -> Result<(), String> {
+        let positive = [
+            ("virt_zoom", self.virt_zoom),
+            ("sim_dt", self.sim_dt),
+            ("size_particle", self.size_particle as f64),
+            ("camera_fov", self.camera_fov as f64),
+        ];
+        for (name, v) in positive {
+            if !(v.is_finite() && v > 0.0) {
+                return Err(format!("{name} has to be a positive number, not {v}"));
+            }
+        }
+        if self.camera_fov >= std::f32::consts::PI {
+            return Err(format!("camera_fov is in radians and has to be under pi, not {}", self.camera_fov));
+        }
+        return Ok(());
+    }
+
+    pub fn virt_to_world_mut(&self, c: &chaos::Coord, t: &mut Transform) {
+        t.translation.x = (c.x * self.virt_zoom) as f32;
+        t.translation.y = (c.y * self.virt_zoom) as f32;
+        t.translation.z = (c.z * self.virt_zoom) as f32;
+    }
+}
+
+#[derive(Default)]
+struct SettingsLoader;
+
+impl AssetLoader for SettingsLoader {
+    type Asset = SimSettings;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<SimSettings, Self::Error> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).await?;
+        let settings: SimSettings = ron::from_str(&text)?;
+        settings.validate()?;
+        return Ok(settings);
+    }
+
+    //not plain "ron", that would claim every ron asset - the handle's type is what finds this loader for SETTINGS_FILE anyway
+    fn extensions(&self) -> &[&str] {
+        return &["settings.ron"];
+    }
+}
+
+#[derive(Resource)]
+pub struct SettingsFile(Handle<SimSettings>);
+
+fn load_settings(mut cmd: Commands, asset_server: Res<AssetServer>) {
+    cmd.insert_resource(SettingsFile(asset_server.load(SETTINGS_FILE)));
+}
+
+//whether the file has been read (or failed to), and its values are in SimSettings
+pub fn settings_loaded(
+    file: Res<SettingsFile>,
+    asset_server: Res<AssetServer>,
+    assets: Res<Assets<SimSettings>>,
+    settings: Res<SimSettings>,
+) -> bool {
+    if let Some(loaded) = assets.get(&file.0) {
+        return *loaded == *settings;
+    }
+    return matches!(asset_server.load_state(&file.0), bevy::asset::LoadState::Failed(_));
+}
+
+//a broken file (or one with a zero zoom, negative dt...) gets logged by the asset server, and the last good values stay
+fn follow_settings_file(
+    mut events: EventReader<AssetEvent<SimSettings>>,
+    file: Res<SettingsFile>,
+    assets: Res<Assets<SimSettings>>,
+    mut settings: ResMut<SimSettings>,
+) {
+    for event in events.read() {
+        if !event.is_loaded_with_dependencies(&file.0) && !event.is_modified(&file.0) {
+            continue;
+        }
+        if let Some(loaded) = assets.get(&file.0) {
+            info!("settings: {loaded:?}");
+            settings.set_if_neq(loaded.clone());
+        }
+    }
+}
+
+//everything that was set up from the old values, redone with the new ones
+pub fn apply_settings(
+    settings: Res<SimSettings>,
+    //the zoom and sim_dt applied last time
+    mut applied: Local<(Option<f64>, Option<f64>)>,
+    mut eq: ResMut<ChaosEquationResource>,
+    mut q_camera: Query<(&mut PanOrbitState, &mut Transform, &mut Projection)>,
+    mut light: ResMut<AmbientLight>,
+    cube: Res<CubeMeshMaterial>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    //only when it's the dt that changed, a loaded snapshot's stays otherwise
+    if applied.1.replace(settings.sim_dt) != Some(settings.sim_dt) {
+        eq.sim_dt = settings.sim_dt;
+    }
+    light.brightness = settings.light_strength;
+    let size = settings.size_particle;
+    meshes.insert(&cube.0, Cuboid::new(size, size, size).into());
+    //the camera moves with the zoom, so the attractor stays where it was on screen and only the particles and axes change size
+    let scale = (settings.virt_zoom / applied.0.unwrap_or(settings.virt_zoom)) as f32;
+    applied.0 = Some(settings.virt_zoom);
+    for (mut state, mut transform, mut projection) in &mut q_camera {
+        state.center *= scale;
+        state.radius *= scale;
+        transform.translation = state.center + transform.back() * state.radius;
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = settings.camera_fov;
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<SimSettings>()
+            .init_asset::<SimSettings>()
+            .init_asset_loader::<SettingsLoader>()
+            .add_systems(Startup, load_settings)
+            .add_systems(Update, follow_settings_file)
+            .add_systems(Update, apply_settings.after(follow_settings_file));
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/*
+    Snapshots
+    the whole state of a simulation as a ron file - which system with which parameters, how it's stepped,
+    where the camera is and every particle - so it can be loaded back exactly, on any machine
+*/
+
+//bump whenever the format changes; older versions still load, fields they don't have come out as defaults
+//  2: added `rng`
+//  3: added `sim_dt`
+//  4: camera center and radius in virtual units, so they still fit the particles under another virt_zoom
+pub const SNAPSHOT_VERSION: u32 = 4;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Snapshot {
+    pub version: u32,
+    //registry id of the system
+    pub system: String,
+    //by name, so files survive parameters getting reordered or added
+    pub params: BTreeMap<String, f64>,
+    //integrator name, as shown on screen
+    pub integrator: String,
+    pub steps: u8,
+    //dt is sim_dt * 2^dt_mult, and sim_dt comes from the settings - the file's is used instead, so it runs at the same dt
+    #[serde(default)]
+    pub sim_dt: Option<f64>,
+    pub dt_mult: f32,
+    pub camera: CameraSnapshot,
+    pub particles: Vec<ParticleSnapshot>,
+    #[serde(default)]
+    pub rng: Option<RngSnapshot>,
+}
+
+//the pan-orbit camera state - center and radius in virtual units, or world units before version 4
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct CameraSnapshot {
+    pub center: [f32; 3],
+    pub radius: f32,
+    pub pitch: f32,
+    pub yaw: f32,
+    pub upside_down: bool,
+}
+
+//a particle's id and its virtual coordinates
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct ParticleSnapshot {
+    pub id: u64,
+    pub at: [f64; 3],
+}
+
+//the spawn rng's seed and how far into its stream it is, so spawns after loading are the ones that would've come next
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct RngSnapshot {
+    pub seed: u64,
+    pub word_pos: u64,
+}
+
+//just enough to check the version before trying to read the rest
+#[derive(Deserialize)]
+struct Versioned {
+    version: u32,
+}
+
+fn invalid(e: impl ToString) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+}
+
+impl Snapshot {
+    pub fn to_ron(&self) -> String {
+        //every f64 gets written as the shortest string that parses back to the same value, so nothing is lost
+        return ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("snapshots only hold plain data");
+    }
+
+    pub fn from_ron(text: &str) -> io::Result<Snapshot> {
+        let versioned: Versioned = ron::from_str(text).map_err(invalid)?;
+        if versioned.version == 0 || versioned.version > SNAPSHOT_VERSION {
+            return Err(invalid(format!(
+                "snapshot is version {}, this build reads versions 1 to {SNAPSHOT_VERSION}", versioned.version,
+            )));
+        }
+        return ron::from_str(text).map_err(invalid);
+    }
+
+    //what the camera's center and radius get multiplied by to put them back in world units
+    pub fn camera_scale(&self, virt_zoom: f64) -> f32 {
+        if self.version < 4 {
+            return 1.0;
+        }
+        return virt_zoom as f32;
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        return std::fs::write(path, self.to_ron());
+    }
+
+    pub fn load(path: &Path) -> io::Result<Snapshot> {
+        return Snapshot::from_ron(&std::fs::read_to_string(path)?);
+    }
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Snapshot {
+        return Snapshot {
+            version: SNAPSHOT_VERSION,
+            system: "lorenz".to_string(),
+            params: BTreeMap::from([("rho".to_string(), 28.0), ("beta".to_string(), 8.0 / 3.0)]),
+            integrator: "RK4".to_string(),
+            steps: 3,
+            sim_dt: Some(0.001),
+            dt_mult: 2.3,
+            camera: CameraSnapshot { center: [0.01, 0.02, 2.5], radius: 4.0, pitch: -0.3, yaw: 0.1, upside_down: false },
+            particles: vec![
+                ParticleSnapshot { id: 0, at: [0.1, -1.0 / 3.0, 1e-300] },
+                ParticleSnapshot { id: 7, at: [-8.485281374238571, std::f64::consts::PI, 27.0] },
+            ],
+            rng: Some(RngSnapshot { seed: u64::MAX, word_pos: 1234 }),
+        };
+    }
+
+    #[test]
+    fn round_trips_exactly() {
+        let original = snapshot();
+        let loaded = Snapshot::from_ron(&original.to_ron()).unwrap();
+        assert_eq!(loaded, original);
+    }
+
+    #[test]
+    fn reads_version_1() {
+        let mut old = snapshot();
+        old.version = 1;
+        old.rng = None;
+        old.sim_dt = None;
+        let text = old.to_ron().replace("    rng: None,\n", "").replace("    sim_dt: None,\n", "");
+        assert!(!text.contains("rng") && !text.contains("sim_dt"), "{text}");
+        assert_eq!(Snapshot::from_ron(&text).unwrap(), old);
+    }
+
+    #[test]
+    fn reads_version_2() {
+        let mut old = snapshot();
+        old.version = 2;
+        old.sim_dt = None;
+        let text = old.to_ron().replace("    sim_dt: None,\n", "");
+        assert!(!text.contains("sim_dt"), "{text}");
+        assert_eq!(Snapshot::from_ron(&text).unwrap(), old);
+    }
+
+    #[test]
+    fn camera_units_depend_on_version() {
+        let mut old = snapshot();
+        assert_eq!(old.camera_scale(100.0), 100.0);
+        old.version = 3;
+        assert_eq!(Snapshot::from_ron(&old.to_ron()).unwrap().camera_scale(100.0), 1.0);
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut newer = snapshot();
+        newer.version = SNAPSHOT_VERSION + 1;
+        let err = Snapshot::from_ron(&newer.to_ron()).unwrap_err();
+        assert!(err.to_string().contains("version"), "{err}");
+    }
+}
@@ -0,0 +1,205 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::chaos_equations::Coord;
+
+/*
+    Spawning
+    where new particles go; all randomness comes from a seeded chacha generator,
+    so the same seed always puts them in exactly the same places
+    (cube, line, grid on any platform - sphere, gaussian and pair go through sin/cos/ln, which can differ in the last bit)
+*/
+
+pub type SpawnRng = ChaCha8Rng;
+
+pub fn rng_from_seed(seed: u64) -> SpawnRng {
+    return ChaCha8Rng::seed_from_u64(seed);
+}
+
+//what a shift+click spawns
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pattern {
+    //uniformly inside a cube, `radius` from the center to a face
+    Cube,
+    //uniformly on the surface of a sphere
+    Sphere,
+    //normally distributed around the center, `radius` is the standard deviation
+    Gaussian,
+    //evenly spaced along x, `radius` to either end
+    Line,
+    //a square grid in the xy plane, `radius` from the center to an edge
+    Grid,
+    //two particles PAIR_SEPARATION apart in a random direction, count and radius don't matter
+    Pair,
+}
+
+impl Pattern {
+    pub const ALL: [Pattern; 6] = [
+        Pattern::Cube,
+        Pattern::Sphere,
+        Pattern::Gaussian,
+        Pattern::Line,
+        Pattern::Grid,
+        Pattern::Pair,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Pattern::Cube => "cube",
+            Pattern::Sphere => "sphere",
+            Pattern::Gaussian => "gaussian",
+            Pattern::Line => "line",
+            Pattern::Grid => "grid",
+            Pattern::Pair => "pair",
+        };
+    }
+
+    pub fn next(&self) -> Self {
+        let i = Pattern::ALL.iter().position(|p| p == self).unwrap_or(0);
+        return Pattern::ALL[(i + 1) % Pattern::ALL.len()];
+    }
+}
+
+pub const SPAWN_COUNT_DEFAULT: usize = 20;
+pub const SPAWN_COUNT_MAX: usize = 65536;
+pub const SPAWN_RADIUS_DEFAULT: f64 = 0.5;
+pub const SPAWN_RADIUS_MIN: f64 = 1.0 / 1024.0;
+pub const SPAWN_RADIUS_MAX: f64 = 64.0;
+//small enough that the pair starts out as one point on screen
+pub const PAIR_SEPARATION: f64 = 1e-8;
+
+//a pattern with its size, in virtual units
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Shape {
+    pub pattern: Pattern,
+    pub count: usize,
+    pub radius: f64,
+}
+
+impl Default for Shape {
+    fn default() -> Self {
+        return Shape { pattern: Pattern::Cube, count: SPAWN_COUNT_DEFAULT, radius: SPAWN_RADIUS_DEFAULT };
+    }
+}
+
+//a uniformly random direction
+fn unit_vector(rng: &mut impl Rng) -> Coord {
+    let z = rng.gen::<f64>() * 2.0 - 1.0;
+    let phi = rng.gen::<f64>() * std::f64::consts::TAU;
+    let r = (1.0 - z * z).sqrt();
+    return Coord { x: r * phi.cos(), y: r * phi.sin(), z };
+}
+
+//a standard normal sample, box-muller
+fn normal(rng: &mut impl Rng) -> f64 {
+    //1 - u is in (0, 1], so the log never sees a 0
+    let u = 1.0 - rng.gen::<f64>();
+    let v = rng.gen::<f64>();
+    return (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos();
+}
+
+//evenly spaced in [-1, 1], the middle for a single one
+fn spread(i: usize, n: usize) -> f64 {
+    if n < 2 {
+        return 0.0;
+    }
+    return i as f64 / (n - 1) as f64 * 2.0 - 1.0;
+}
+
+impl Shape {
+    //the particles of this shape centered on `at`
+    pub fn generate(&self, rng: &mut impl Rng, at: Coord) -> Vec<Coord> {
+        let r = self.radius;
+        let n = self.count;
+        return match self.pattern {
+            Pattern::Cube => (0..n)
+                .map(|_| {
+                    let offset = Coord { x: rng.gen::<f64>() * 2.0 - 1.0, y: rng.gen::<f64>() * 2.0 - 1.0, z: rng.gen::<f64>() * 2.0 - 1.0 };
+                    return at + offset * r;
+                })
+                .collect(),
+            Pattern::Sphere => (0..n).map(|_| at + unit_vector(rng) * r).collect(),
+            Pattern::Gaussian => (0..n)
+                .map(|_| at + Coord { x: normal(rng), y: normal(rng), z: normal(rng) } * r)
+                .collect(),
+            Pattern::Line => (0..n).map(|i| at + Coord { x: spread(i, n) * r, y: 0.0, z: 0.0 }).collect(),
+            Pattern::Grid => {
+                //the smallest square that fits them all, filled row by row
+                let side = (1..).find(|s| s * s >= n).unwrap();
+                (0..n)
+                    .map(|i| at + Coord { x: spread(i % side, side) * r, y: spread(i / side, side) * r, z: 0.0 })
+                    .collect()
+            }
+            Pattern::Pair => vec![at, at + unit_vector(rng) * PAIR_SEPARATION],
+        };
+    }
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(v: &[Coord]) -> Vec<[u64; 3]> {
+        return v.iter().map(|c| [c.x.to_bits(), c.y.to_bits(), c.z.to_bits()]).collect();
+    }
+
+    #[test]
+    fn same_seed_same_bunch() {
+        let at = Coord { x: 1.0, y: -2.0, z: 3.0 };
+        for pattern in Pattern::ALL {
+            let shape = Shape { pattern, ..Shape::default() };
+            let a = shape.generate(&mut rng_from_seed(42), at);
+            let b = shape.generate(&mut rng_from_seed(42), at);
+            assert_eq!(bits(&a), bits(&b), "{}", pattern.name());
+        }
+        let a = Shape::default().generate(&mut rng_from_seed(42), at);
+        let c = Shape::default().generate(&mut rng_from_seed(43), at);
+        assert_ne!(bits(&a), bits(&c));
+    }
+
+    #[test]
+    fn patterns_fit_their_radius() {
+        let at = Coord { x: 1.0, y: -2.0, z: 3.0 };
+        let dist = |c: &Coord| ((c.x - at.x).powi(2) + (c.y - at.y).powi(2) + (c.z - at.z).powi(2)).sqrt();
+        let mut rng = rng_from_seed(7);
+        for pattern in Pattern::ALL {
+            let shape = Shape { pattern, count: 1000, radius: 2.0 };
+            let got = shape.generate(&mut rng, at);
+            let far = got.iter().map(dist).fold(0.0, f64::max);
+            match pattern {
+                Pattern::Pair => {
+                    assert_eq!(got.len(), 2);
+                    assert!((far - PAIR_SEPARATION).abs() < 1e-12);
+                }
+                Pattern::Sphere => assert!(got.iter().all(|c| (dist(c) - 2.0).abs() < 1e-12)),
+                //5 standard deviations along every axis at once, far past what 1000 samples reach
+                Pattern::Gaussian => assert!(far < 2.0 * 5.0 * 3f64.sqrt()),
+                Pattern::Cube => assert!(far <= 2.0 * 3f64.sqrt()),
+                Pattern::Line | Pattern::Grid => assert!(far <= 2.0 * 2f64.sqrt() + 1e-12),
+            }
+            if pattern != Pattern::Pair {
+                assert_eq!(got.len(), 1000, "{}", pattern.name());
+            }
+        }
+        //a grid and a line reach exactly to their ends
+        let line = Shape { pattern: Pattern::Line, count: 3, radius: 2.0 }.generate(&mut rng, at);
+        assert_eq!(line.iter().map(|c| c.x - at.x).collect::<Vec<_>>(), [-2.0, 0.0, 2.0]);
+    }
+
+    #[test]
+    fn bunch_is_pinned() {
+        //if this changes, every saved seed spawns somewhere else - only update it on purpose
+        let first = Shape { count: 2, ..Shape::default() }.generate(&mut rng_from_seed(42), Coord::ZERO);
+        let got = [first[0].x, first[0].y, first[0].z, first[1].x, first[1].y, first[1].z];
+        assert_eq!(got.map(f64::to_bits), PINNED.map(f64::to_bits), "{got:?}");
+    }
+
+    const PINNED: [f64; 6] = [
+        0.18189619230667142, 0.450275407672484, -0.0724835971434803,
+        0.12736052119734032, -0.21140612085881738, -0.35004112970967505,
+    ];
+}
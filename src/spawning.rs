@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+use chaos_core::spawn::{Shape, SPAWN_COUNT_MAX, SPAWN_RADIUS_MAX, SPAWN_RADIUS_MIN};
+
+use crate::keybindings::{Action, Actions};
+
+/*
+    Spawn Patterns
+    what shape a shift+click bunch comes in and how big it is, see chaos_core::spawn
+*/
+
+#[derive(Resource, Default)]
+pub struct SpawnSettings(pub Shape);
+
+//g cycles the pattern, - and = halve/double the count, shift+- and shift+= halve/double the radius
+fn spawn_keybinds(actions: Actions, mut settings: ResMut<SpawnSettings>) {
+    let shape = &mut settings.0;
+    if actions.just_pressed(Action::SpawnPattern) {
+        shape.pattern = shape.pattern.next();
+    }
+    if actions.just_pressed(Action::MoreInBunch) {
+        shape.count = (shape.count * 2).min(SPAWN_COUNT_MAX);
+    } else if actions.just_pressed(Action::FewerInBunch) {
+        shape.count = (shape.count / 2).max(1);
+    }
+    if actions.just_pressed(Action::BiggerBunch) {
+        shape.radius = (shape.radius * 2.0).min(SPAWN_RADIUS_MAX);
+    } else if actions.just_pressed(Action::SmallerBunch) {
+        shape.radius = (shape.radius / 2.0).max(SPAWN_RADIUS_MIN);
+    }
+}
+
+pub struct SpawningPlugin;
+
+impl Plugin for SpawningPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<SpawnSettings>()
+            .add_systems(Update, spawn_keybinds);
+    }
+}
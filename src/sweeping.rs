@@ -0,0 +1,135 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
+
+use chaos_core::bifurcation::{self, Sweep};
+use chaos_core::poincare::Axis;
+
+use crate::chaos_equations as chaos;
+use crate::keybindings::{Action, Actions};
+use crate::recording::RECORDING_DIR;
+use crate::{integrators, ChaosEquationResource, ParamSelection, SystemRegistry};
+
+/*
+    Bifurcation Sweeps
+    b sweeps the parameter the arrow keys are on over its whole range, in the background, for the current system,
+    integrator and dt, and writes the diagram as a png to RECORDING_DIR - see chaos_core::bifurcation
+*/
+
+pub const BIFURCATION_COLUMNS: usize = 800;
+pub const BIFURCATION_HEIGHT: usize = 600;
+//per column, the first half of them to settle
+pub const BIFURCATION_STEPS: usize = 20_000;
+
+#[derive(Resource)]
+pub struct Bifurcation {
+    //whose maxima get plotted
+    pub axis: Axis,
+    task: Option<Task<String>>,
+    //columns finished by the running sweep
+    pub done: Arc<AtomicUsize>,
+    //what the last sweep did, for the overlay
+    pub status: Option<String>,
+}
+
+impl Default for Bifurcation {
+    fn default() -> Self {
+        return Bifurcation { axis: Axis::Z, task: None, done: Arc::default(), status: None };
+    }
+}
+
+impl Bifurcation {
+    pub fn is_running(&self) -> bool {
+        return self.task.is_some();
+    }
+}
+
+//b starts a sweep unless one is running, shift+b picks the coordinate
+fn bifurcation_keybinds(
+    actions: Actions,
+    mut bifurcation: ResMut<Bifurcation>,
+    eq: Res<ChaosEquationResource>,
+    registry: Res<SystemRegistry>,
+    selection: Res<ParamSelection>,
+) {
+    if actions.just_pressed(Action::BifurcationAxis) {
+        bifurcation.axis = bifurcation.axis.next();
+        return;
+    }
+    if !actions.just_pressed(Action::Bifurcation) {
+        return;
+    }
+    let system = registry.0.get(eq.system).clone();
+    let Some(param) = system.params.get(selection.0) else {
+        bifurcation.status = Some(format!("{} has no parameters to sweep", system.name));
+        return;
+    };
+    if bifurcation.is_running() {
+        return;
+    }
+    let sweep = Sweep {
+        param: selection.0,
+        from: param.min,
+        to: param.max,
+        columns: BIFURCATION_COLUMNS,
+        axis: bifurcation.axis,
+        start: system.framing.center + chaos::Coord { x: 0.1, y: 0.1, z: 0.1 },
+        dt: eq.dt(),
+        transient: BIFURCATION_STEPS / 2,
+        steps: BIFURCATION_STEPS / 2,
+    };
+    let params = eq.params.clone();
+    let integrator = integrators::INTEGRATORS[eq.integrator];
+    let done = Arc::new(AtomicUsize::new(0));
+    bifurcation.done = done.clone();
+    bifurcation.task = Some(AsyncComputeTaskPool::get().spawn(async move {
+        //leave a thread for the simulation itself
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get().saturating_sub(1).max(1));
+        let columns = sweep.run(&system, &params, integrator, threads, &done);
+        let (pixels, (lo, hi)) = bifurcation::render(&columns, BIFURCATION_HEIGHT);
+
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let name = system.params[sweep.param].name;
+        let path = PathBuf::from(RECORDING_DIR).join(format!("{}-bifurcation-{name}-{secs}.png", system.id));
+        let written = std::fs::create_dir_all(RECORDING_DIR)
+            .and_then(|_| File::create(&path))
+            .and_then(|file| bifurcation::write_png(BufWriter::new(file), &pixels, columns.len(), BIFURCATION_HEIGHT));
+        return match written {
+            Ok(()) => format!(
+                "wrote {} - {name} {} to {}, {} max {lo:.3} to {hi:.3}",
+                path.display(), sweep.from, sweep.to, sweep.axis.name(),
+            ),
+            Err(e) => {
+                error!("couldn't write {}: {e}", path.display());
+                format!("couldn't write {}: {e}", path.display())
+            }
+        };
+    }));
+}
+
+fn poll_bifurcation(mut bifurcation: ResMut<Bifurcation>) {
+    let Some(task) = bifurcation.task.as_mut() else {
+        return;
+    };
+    if let Some(status) = block_on(future::poll_once(task)) {
+        bifurcation.status = Some(status);
+        bifurcation.task = None;
+    }
+}
+
+pub struct SweepingPlugin;
+
+impl Plugin for SweepingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Bifurcation>()
+            .add_systems(Update, bifurcation_keybinds)
+            .add_systems(Update, poll_bifurcation);
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::chaos_equations as chaos;
+use crate::keybindings::{Action, Actions};
+use crate::settings::SimSettings;
+use crate::{simulation_running, vmove_particle_system, Particle, COLOR_PARTICLE};
+
+/*
+    Trails
+    every particle can drag a fading line behind it, made of its last few virtual positions
+*/
+
+pub const TRAIL_LENGTH_DEFAULT: usize = 256;
+pub const TRAIL_LENGTH_MIN: usize = 2;
+pub const TRAIL_LENGTH_MAX: usize = 8192;
+pub const TRAIL_FADE_STEP: f32 = 0.25;
+pub const TRAIL_FADE_MAX: f32 = 8.0;
+
+#[derive(Resource)]
+pub struct TrailSettings {
+    pub enabled: bool,
+    //how many positions every trail keeps
+    pub length: usize,
+    //alpha along the trail is (age fraction)^fade - 0 is no fade at all, 1 is linear
+    pub fade: f32,
+}
+
+impl Default for TrailSettings {
+    fn default() -> Self {
+        return TrailSettings {
+            enabled: false,
+            length: TRAIL_LENGTH_DEFAULT,
+            fade: 1.0,
+        };
+    }
+}
+
+//ring buffer of the latest positions of a particle, oldest first
+#[derive(Component, Default)]
+pub struct Trail(pub VecDeque<chaos::Coord>);
+
+//give every particle a trail while trails are on, take them away when they're off
+fn attach_trails(
+    mut cmd: Commands,
+    settings: Res<TrailSettings>,
+    untrailed: Query<Entity, (With<Particle>, Without<Trail>)>,
+    trailed: Query<Entity, With<Trail>>,
+) {
+    if settings.enabled {
+        for entity in &untrailed {
+            cmd.entity(entity).insert(Trail::default());
+        }
+    } else if settings.is_changed() {
+        for entity in &trailed {
+            cmd.entity(entity).remove::<Trail>();
+        }
+    }
+}
+
+fn record_trails(settings: Res<TrailSettings>, mut particles: Query<(&Particle, &mut Trail)>) {
+    for (particle, mut trail) in &mut particles {
+        trail.0.push_back(particle.0);
+        while trail.0.len() > settings.length {
+            trail.0.pop_front();
+        }
+    }
+}
+
+fn draw_trails(mut gizmos: Gizmos, settings: Res<TrailSettings>, sim_settings: Res<SimSettings>, trails: Query<&Trail>) {
+    for trail in &trails {
+        let n = trail.0.len();
+        if n < 2 {
+            continue;
+        }
+        gizmos.linestrip_gradient(trail.0.iter().enumerate().map(|(i, c)| {
+            let age = (i + 1) as f32 / n as f32;
+            let alpha = age.powf(settings.fade);
+            return (sim_settings.virt_to_world(c).translation, COLOR_PARTICLE.with_alpha(alpha));
+        }));
+    }
+}
+
+//t toggles, ; and ' make trails shorter/longer, shift+; and shift+' give them less/more fade
+fn trail_keybinds(actions: Actions, mut settings: ResMut<TrailSettings>) {
+    if actions.just_pressed(Action::ToggleTrails) {
+        settings.enabled = !settings.enabled;
+    }
+    if actions.just_pressed(Action::LongerTrails) {
+        settings.length = (settings.length * 2).min(TRAIL_LENGTH_MAX);
+    } else if actions.just_pressed(Action::ShorterTrails) {
+        settings.length = (settings.length / 2).max(TRAIL_LENGTH_MIN);
+    }
+    if actions.just_pressed(Action::MoreFade) {
+        settings.fade = (settings.fade + TRAIL_FADE_STEP).min(TRAIL_FADE_MAX);
+    } else if actions.just_pressed(Action::LessFade) {
+        settings.fade = (settings.fade - TRAIL_FADE_STEP).max(0.0);
+    }
+}
+
+pub struct TrailsPlugin;
+
+impl Plugin for TrailsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TrailSettings>()
+            .add_systems(FixedUpdate, record_trails.after(vmove_particle_system).run_if(simulation_running))
+            .add_systems(Update, attach_trails)
+            .add_systems(Update, trail_keybinds)
+            .add_systems(Update, draw_trails);
+    }
+}
@@ -0,0 +1,219 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::chaos_equations::Coord;
+use crate::expression;
+use crate::integrators;
+use crate::registry::{ChaosSystem, Equation, Framing, ParamDef, Registry};
+
+/*
+    User Systems
+    systems typed in the expression language (see expression.rs) instead of written in rust, from a ron file like
+
+        [
+            (
+                id: "lorenz_typed",
+                name: "Lorenz, typed in",
+                equations: "
+                    dx = s*(y - x)
+                    dy = x*(r - z) - y
+                    dz = x*y - b*z
+                    s = 10; r = 28; b = 8/3
+                ",
+                dt: 0.005,
+                center: (0, 0, 25),
+                radius: 40,
+            ),
+        ]
+
+    everything but the id and the equations can be left out - without a center and radius, the view gets fitted to a trial run
+*/
+
+//what the app loads on startup if it's there and no other file was given
+pub const USER_SYSTEMS_FILE: &str = "equations.ron";
+
+pub const DEFAULT_DT: f64 = 0.01;
+//the view when the trial run goes nowhere useful
+pub const FALLBACK_FRAMING: Framing = Framing::new(0.0, 0.0, 0.0, 20.0);
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct UserSystem {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub equations: String,
+    #[serde(default = "default_dt")]
+    pub dt: f64,
+    #[serde(default)]
+    pub center: Option<[f64; 3]>,
+    #[serde(default)]
+    pub radius: Option<f64>,
+}
+
+fn default_dt() -> f64 {
+    return DEFAULT_DT;
+}
+
+//the registry hands out &'static everywhere, so a system made at runtime has its strings leaked -
+//a few bytes per system, and systems never get removed anyway
+fn leak(s: String) -> &'static str {
+    return Box::leak(s.into_boxed_str());
+}
+
+//a range for the arrow keys to slide through, since the language only gives a default: three times its size either way
+fn param_def(name: &str, default: f64) -> ParamDef {
+    let spread = (default.abs() * 3.0).max(1.0);
+    return ParamDef::new(leak(name.to_string()), default, default - spread, default + spread);
+}
+
+//the bounding box of a trial run from just off the origin, once it's settled
+fn fit_framing(system: &ChaosSystem) -> Framing {
+    let params = system.default_params();
+    let rk4 = integrators::find("rk4").unwrap();
+    let f = |c: &Coord| system.derivative(c, &params);
+    let mut at = Coord { x: 0.1, y: 0.1, z: 0.1 };
+    let (mut lo, mut hi) = ([f64::INFINITY; 3], [f64::NEG_INFINITY; 3]);
+    for step in 0..10_000 {
+        at = rk4.step(&f, &at, system.dt);
+        if step < 5_000 {
+            continue;
+        }
+        for (i, v) in [at.x, at.y, at.z].into_iter().enumerate() {
+            (lo[i], hi[i]) = (lo[i].min(v), hi[i].max(v));
+        }
+    }
+    let size = (0..3).map(|i| (hi[i] - lo[i]).powi(2)).sum::<f64>().sqrt() / 2.0;
+    if !size.is_finite() {
+        return FALLBACK_FRAMING;
+    }
+    //settled onto a fixed point, so there's no size to go by
+    return Framing::new((lo[0] + hi[0]) / 2.0, (lo[1] + hi[1]) / 2.0, (lo[2] + hi[2]) / 2.0, size.max(1.0));
+}
+
+impl UserSystem {
+    //the equations on their own, as typed into the app
+    pub fn typed(id: String, name: String, equations: String) -> Self {
+        return UserSystem { id, name: Some(name), equations, dt: DEFAULT_DT, center: None, radius: None };
+    }
+
+    pub fn build(&self) -> Result<ChaosSystem, String> {
+        let equations = expression::compile(&self.equations).map_err(|e| e.to_string())?;
+        if !self.dt.is_finite() || self.dt <= 0.0 {
+            return Err(format!("dt has to be above 0, got {}", self.dt));
+        }
+        let params: Vec<ParamDef> = equations.params.iter().map(|(name, default)| param_def(name, *default)).collect();
+        let mut system = ChaosSystem {
+            id: leak(self.id.clone()),
+            name: leak(self.name.clone().unwrap_or_else(|| self.id.clone())),
+            params: Box::leak(params.into_boxed_slice()),
+            eq: Equation::Typed(Arc::new(equations)),
+            dt: self.dt,
+            framing: FALLBACK_FRAMING,
+            equilibria: None,
+        };
+        system.framing = match (self.center, self.radius) {
+            (Some([x, y, z]), Some(radius)) => Framing::new(x, y, z, radius),
+            (center, radius) => {
+                let fitted = fit_framing(&system);
+                let center = center.map_or(fitted.center, |[x, y, z]| Coord { x, y, z });
+                Framing { center, radius: radius.unwrap_or(fitted.radius) }
+            }
+        };
+        return Ok(system);
+    }
+}
+
+pub fn from_ron(text: &str) -> Result<Vec<UserSystem>, String> {
+    //so the optional fields can be written as they are, without `Some(...)` around them
+    let options = ron::Options::default().with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME);
+    return options.from_str(text).map_err(|e| e.to_string());
+}
+
+//every system in the file, built and added to `registry` - either all of them or, on the first problem, none
+//returns how many there were
+pub fn load(path: &Path, registry: &mut Registry) -> Result<usize, String> {
+    let context = |e: String| format!("{}: {e}", path.display());
+    let text = std::fs::read_to_string(path).map_err(|e| context(e.to_string()))?;
+    let mut systems = Vec::new();
+    for user in from_ron(&text).map_err(context)? {
+        let taken = registry.find(&user.id).is_some() || systems.iter().any(|s: &ChaosSystem| s.id == user.id);
+        if taken {
+            return Err(context(format!("there's already a system called `{}`", user.id)));
+        }
+        systems.push(user.build().map_err(|e| context(format!("`{}`: {e}", user.id)))?);
+    }
+    let count = systems.len();
+    for system in systems {
+        registry.register(system);
+    }
+    return Ok(count);
+}
+
+/*
+    Tests
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"[
+        (
+            id: "lorenz_typed",
+            name: "Lorenz, typed in",
+            equations: "
+                dx = s*(y - x)
+                dy = x*(r - z) - y
+                dz = x*y - b*z
+                s = 10; r = 28; b = 8/3
+            ",
+            dt: 0.005,
+            center: (0, 0, 25),
+            radius: 40,
+        ),
+        (id: "spiral", equations: "dx = -y - x/10; dy = x - y/10; dz = -z"),
+    ]"#;
+
+    #[test]
+    fn reads_a_file() {
+        let users = from_ron(FILE).unwrap();
+        let lorenz = users[0].build().unwrap();
+        assert_eq!((lorenz.id, lorenz.name, lorenz.dt), ("lorenz_typed", "Lorenz, typed in", 0.005));
+        assert_eq!(lorenz.params.iter().map(|p| p.name).collect::<Vec<_>>(), ["s", "r", "b"]);
+        assert_eq!((lorenz.params[1].default, lorenz.params[1].min, lorenz.params[1].max), (28.0, -56.0, 112.0));
+        assert_eq!((lorenz.framing.center.z, lorenz.framing.radius), (25.0, 40.0));
+
+        //left out: the name is the id, dt the default, and the view fitted to where it ends up - the origin
+        let spiral = users[1].build().unwrap();
+        assert_eq!((spiral.name, spiral.dt), ("spiral", DEFAULT_DT));
+        let center = spiral.framing.center;
+        assert!(center.x.abs() < 1e-3 && center.y.abs() < 1e-3 && center.z.abs() < 1e-3);
+        assert_eq!(spiral.framing.radius, 1.0);
+    }
+
+    #[test]
+    fn rejects_taken_ids_and_bad_equations() {
+        let dir = std::env::temp_dir().join(format!("chaos-user-systems-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(USER_SYSTEMS_FILE);
+        let mut registry = Registry::builtin();
+        let before = registry.len();
+
+        std::fs::write(&path, r#"[(id: "lorenz", equations: "dx = 0; dy = 0; dz = 0")]"#).unwrap();
+        let error = load(&path, &mut registry).unwrap_err();
+        assert!(error.ends_with("there's already a system called `lorenz`"), "{error}");
+
+        std::fs::write(&path, r#"[(id: "fine", equations: "dx = 0; dy = 0; dz = 0"), (id: "broken", equations: "dx = y +")]"#).unwrap();
+        let error = load(&path, &mut registry).unwrap_err();
+        assert!(error.ends_with("`broken`: line 1, column 9: expected a number, a name or `(`, got the end"), "{error}");
+        //nothing from a file with a problem in it gets added
+        assert_eq!(registry.len(), before);
+
+        std::fs::write(&path, FILE).unwrap();
+        assert_eq!(load(&path, &mut registry), Ok(2));
+        assert_eq!(registry.find("spiral"), Some(before + 1));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}